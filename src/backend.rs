@@ -0,0 +1,813 @@
+use crate::commit::{CommitInfo, GitError};
+use anyhow::{Context, Result, bail};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    ffi::OsStr,
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Field and record separators used when asking git log for machine-readable output
+const LOG_FIELD_SEP: char = '\x1f';
+const LOG_RECORD_SEP: char = '\x1e';
+const LOG_FORMAT: &str = "--format=%H%x1f%P%x1f%aN%x1f%at%x1f%s%x1f%b%x1e";
+
+/// `git log` options that replace `LOG_FORMAT` or add lines to its output
+const LOG_OUTPUT_OPTIONS: [&str; 16] = [
+    "--oneline",
+    "--pretty",
+    "--format",
+    "--graph",
+    "--patch",
+    "-p",
+    "-u",
+    "--stat",
+    "--numstat",
+    "--shortstat",
+    "--summary",
+    "--name-only",
+    "--name-status",
+    "--raw",
+    "--show-signature",
+    "-z",
+];
+
+/// What git reported about the hooks it ran during an invocation
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum HookOutcome {
+    /// Git did not report on its hooks (no trace2 support, or a fake backend)
+    #[default]
+    Unknown,
+    /// Every hook git ran, if any, exited with status 0
+    Passed,
+    /// The named hook exited with a non-zero status
+    Failed(String),
+}
+
+/// Captured result of a git invocation
+#[derive(Debug, Clone, PartialEq)]
+pub struct GitOutput {
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub hooks: HookOutcome,
+}
+
+impl GitOutput {
+    /// A successful invocation that printed `stdout`
+    pub fn success(stdout: impl Into<String>) -> Self {
+        Self {
+            exit_code: Some(0),
+            stdout: stdout.into(),
+            stderr: String::new(),
+            hooks: HookOutcome::Unknown,
+        }
+    }
+
+    /// A failed invocation with the given exit code and output
+    pub fn failure(exit_code: i32, stdout: impl Into<String>, stderr: impl Into<String>) -> Self {
+        Self {
+            exit_code: Some(exit_code),
+            stdout: stdout.into(),
+            stderr: stderr.into(),
+            hooks: HookOutcome::Unknown,
+        }
+    }
+
+    /// The same output, with what git reported about its hooks
+    pub fn with_hooks(mut self, hooks: HookOutcome) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    /// Whether git exited with status 0
+    pub fn is_success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// The git operations amoji needs, so flows can run against a real repository or a fake
+pub trait GitBackend {
+    /// Whether the backend points at a git work tree
+    fn is_repository(&self) -> Result<bool>;
+
+    /// Output of `git status --porcelain=v2 -z`
+    fn status(&self) -> Result<String>;
+
+    /// Run `git` with a full commit argv (starting with "commit") and return the raw output
+    /// along with how the hooks it ran exited
+    fn commit(&self, args: &[String]) -> Result<GitOutput>;
+
+    /// Commits selected by revision arguments (e.g. ["origin/main..HEAD"]), newest first
+    /// The arguments may end with `--` and paths to only list commits touching those paths
+    fn log(&self, revisions: &[&str]) -> Result<Vec<CommitInfo>>;
+
+    /// Tags whose commits are reachable from `revision`, most recently created first
+    fn tags(&self, revision: &str) -> Result<Vec<String>>;
+
+    /// Output of `git diff` with the given arguments (e.g. ["--cached"])
+    fn diff(&self, args: &[&str]) -> Result<String>;
+
+    /// Contents of a blob such as "HEAD:Cargo.toml" or ":Cargo.toml", or None when it doesn't exist
+    fn show(&self, object: &str) -> Result<Option<String>>;
+
+    /// A git config value, or None when the key is unset
+    fn config_get(&self, key: &str) -> Result<Option<String>>;
+
+    /// The directory git runs hooks from
+    fn hooks_dir(&self) -> Result<PathBuf>;
+
+    /// Contents of a state file in the git directory such as "MERGE_HEAD", or None when it doesn't exist
+    fn git_file(&self, name: &str) -> Result<Option<String>>;
+
+    /// The top-level directory of the work tree
+    fn work_tree(&self) -> Result<PathBuf>;
+}
+
+/// Environment variables that select a repository, ignored when one is given explicitly
+const REPOSITORY_ENV_VARS: [&str; 4] = [
+    "GIT_DIR",
+    "GIT_WORK_TREE",
+    "GIT_INDEX_FILE",
+    "GIT_COMMON_DIR",
+];
+
+/// Backend that runs the `git` binary as a subprocess
+#[derive(Debug, Clone, Default)]
+pub struct ProcessBackend {
+    repo: Option<PathBuf>,
+}
+
+impl ProcessBackend {
+    /// Run git in the current directory, honoring `$GIT_DIR` and `$GIT_WORK_TREE`
+    pub fn new() -> Self {
+        Self { repo: None }
+    }
+
+    /// Run git as if started in `repo` (like `git -C <repo>`)
+    pub fn in_repo(repo: impl Into<PathBuf>) -> Self {
+        Self {
+            repo: Some(repo.into()),
+        }
+    }
+
+    /// The repository path given to `in_repo`, if any
+    pub fn repo(&self) -> Option<&Path> {
+        self.repo.as_deref()
+    }
+
+    fn command<S: AsRef<OsStr>>(&self, args: &[S]) -> Command {
+        let mut command = Command::new("git");
+        if let Some(repo) = &self.repo {
+            command.arg("-C").arg(repo);
+            // Hooks export GIT_DIR and friends, which would otherwise win over `-C`
+            for var in REPOSITORY_ENV_VARS {
+                command.env_remove(var);
+            }
+        }
+        command.args(args);
+        command
+    }
+
+    /// Run git and capture its output, reporting a missing git binary as `GitError::GitNotFound`
+    pub fn run<S: AsRef<OsStr>>(&self, args: &[S]) -> Result<GitOutput> {
+        Self::capture(self.command(args))
+    }
+
+    fn capture(mut command: Command) -> Result<GitOutput> {
+        let output = match command.output() {
+            Ok(output) => output,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Err(GitError::GitNotFound.into());
+            }
+            Err(e) => return Err(e).context("Failed to execute git command"),
+        };
+
+        Ok(GitOutput {
+            exit_code: output.status.code(),
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            hooks: HookOutcome::Unknown,
+        })
+    }
+
+    /// Run git with its trace2 event stream enabled, and read from it how the hooks exited
+    fn run_traced(&self, args: &[String]) -> Result<GitOutput> {
+        static TRACES: AtomicUsize = AtomicUsize::new(0);
+        let trace = std::env::temp_dir().join(format!(
+            "amoji-trace2-{}-{}.json",
+            std::process::id(),
+            TRACES.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let mut command = self.command(args);
+        command.env("GIT_TRACE2_EVENT", &trace);
+        let output = Self::capture(command);
+
+        let events = fs::read_to_string(&trace);
+        let _ = fs::remove_file(&trace);
+        let hooks = events.map_or(HookOutcome::Unknown, |events| {
+            Self::parse_hook_events(&events)
+        });
+        Ok(output?.with_hooks(hooks))
+    }
+
+    /// Hook outcomes from trace2 events, one JSON object per line
+    /// A hook is a `child_start` with class "hook", and its exit code is on the `child_exit` with the same id
+    fn parse_hook_events(events: &str) -> HookOutcome {
+        let mut session = None;
+        let mut hooks = HashMap::new();
+
+        for line in events.lines() {
+            let Ok(event) = serde_json::from_str::<serde_json::Value>(line) else {
+                continue;
+            };
+            let Some(sid) = event["sid"].as_str() else {
+                continue;
+            };
+            // Git commands run by the hooks append to the same file under a nested session id
+            if *session.get_or_insert_with(|| sid.to_string()) != sid {
+                continue;
+            }
+
+            let child_id = event["child_id"].as_i64();
+            match event["event"].as_str() {
+                Some("child_start") if event["child_class"] == "hook" => {
+                    let name = event["hook_name"].as_str().unwrap_or("hook");
+                    hooks.insert(child_id, name.to_string());
+                }
+                Some("child_exit") => {
+                    if let Some(name) = hooks.remove(&child_id)
+                        && event["code"].as_i64() != Some(0)
+                    {
+                        return HookOutcome::Failed(name);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if session.is_some() {
+            HookOutcome::Passed
+        } else {
+            HookOutcome::Unknown
+        }
+    }
+
+    /// Run git and return its stdout, failing with the stderr on a non-zero exit
+    pub fn run_checked(&self, args: &[&str]) -> Result<String> {
+        let output = self.run(args)?;
+
+        if output.is_success() {
+            Ok(output.stdout)
+        } else {
+            Err(GitError::CommandFailed(output.stderr).into())
+        }
+    }
+
+    /// Resolve a path inside the git directory, like `git rev-parse --git-path`
+    fn git_path(&self, name: &str) -> Result<PathBuf> {
+        let path = PathBuf::from(self.run_checked(&["rev-parse", "--git-path", name])?.trim());
+
+        // Relative paths are relative to the directory git ran in
+        match &self.repo {
+            Some(repo) if path.is_relative() => Ok(repo.join(path)),
+            _ => Ok(path),
+        }
+    }
+
+    /// Full `git log` argv for the given arguments, in `LOG_FORMAT`
+    /// Options that change the output format are rejected, as `parse_log` could not read it
+    fn log_args<'a>(revisions: &[&'a str]) -> Result<Vec<&'a str>> {
+        let options = revisions.iter().take_while(|arg| **arg != "--");
+        for arg in options {
+            let name = arg.split_once('=').map_or(*arg, |(name, _)| name);
+            if LOG_OUTPUT_OPTIONS.contains(&name) {
+                bail!("`git log` option {arg} changes the output amoji reads and is not supported");
+            }
+        }
+
+        let mut args = vec!["log", LOG_FORMAT];
+        args.extend_from_slice(revisions);
+        if !revisions.contains(&"--") {
+            args.push("--");
+        }
+        Ok(args)
+    }
+
+    /// Parse the output of `git log` in `LOG_FORMAT`
+    fn parse_log(output: &str) -> Vec<CommitInfo> {
+        output
+            .split(LOG_RECORD_SEP)
+            .filter_map(|record| {
+                let mut fields = record.trim_start_matches('\n').split(LOG_FIELD_SEP);
+                let sha = fields.next().filter(|sha| !sha.is_empty())?;
+                let parents = fields
+                    .next()?
+                    .split_whitespace()
+                    .map(String::from)
+                    .collect();
+                let author = fields.next()?;
+                let timestamp = fields.next()?.parse().unwrap_or_default();
+                let subject = fields.next()?.to_string();
+                let body = fields.next().unwrap_or_default().trim_end().to_string();
+                Some(CommitInfo {
+                    sha: sha.to_string(),
+                    parents,
+                    author: author.to_string(),
+                    timestamp,
+                    subject,
+                    body,
+                })
+            })
+            .collect()
+    }
+}
+
+impl GitBackend for ProcessBackend {
+    fn is_repository(&self) -> Result<bool> {
+        Ok(self
+            .run(&["rev-parse", "--is-inside-work-tree"])?
+            .is_success())
+    }
+
+    fn status(&self) -> Result<String> {
+        self.run_checked(&["status", "--porcelain=v2", "-z"])
+    }
+
+    fn commit(&self, args: &[String]) -> Result<GitOutput> {
+        self.run_traced(args)
+    }
+
+    fn log(&self, revisions: &[&str]) -> Result<Vec<CommitInfo>> {
+        let output = self.run_checked(&Self::log_args(revisions)?)?;
+        Ok(Self::parse_log(&output))
+    }
+
+    fn tags(&self, revision: &str) -> Result<Vec<String>> {
+        let output =
+            self.run_checked(&["tag", "--list", "--sort=-creatordate", "--merged", revision])?;
+        Ok(output.lines().map(String::from).collect())
+    }
+
+    fn diff(&self, args: &[&str]) -> Result<String> {
+        let mut diff_args = vec!["diff"];
+        diff_args.extend_from_slice(args);
+        self.run_checked(&diff_args)
+    }
+
+    fn show(&self, object: &str) -> Result<Option<String>> {
+        let output = self.run(&["cat-file", "blob", object])?;
+
+        // Missing paths and an unborn HEAD both exit with 128
+        match output.exit_code {
+            Some(0) => Ok(Some(output.stdout)),
+            Some(128) => Ok(None),
+            _ => Err(GitError::CommandFailed(output.stderr).into()),
+        }
+    }
+
+    fn config_get(&self, key: &str) -> Result<Option<String>> {
+        let output = self.run(&["config", "--get", key])?;
+
+        // `git config --get` exits with 1 when the key does not exist
+        match output.exit_code {
+            Some(0) => Ok(Some(output.stdout.trim().to_string())),
+            Some(1) => Ok(None),
+            _ => Err(GitError::CommandFailed(output.stderr).into()),
+        }
+    }
+
+    fn hooks_dir(&self) -> Result<PathBuf> {
+        // Honors `core.hooksPath`, `$GIT_DIR` and the common directory of linked worktrees
+        self.git_path("hooks")
+    }
+
+    fn git_file(&self, name: &str) -> Result<Option<String>> {
+        let path = self.git_path(name)?;
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    fn work_tree(&self) -> Result<PathBuf> {
+        let path = self.run_checked(&["rev-parse", "--show-toplevel"])?;
+        Ok(PathBuf::from(path.trim()))
+    }
+}
+
+/// In-memory backend with canned answers that records every call, for tests
+#[derive(Debug)]
+pub struct FakeBackend {
+    is_repository: bool,
+    status: String,
+    commit_output: GitOutput,
+    logs: HashMap<String, Vec<CommitInfo>>,
+    tags: Vec<String>,
+    diffs: HashMap<String, String>,
+    files: HashMap<String, String>,
+    config: HashMap<String, String>,
+    git_files: HashMap<String, String>,
+    hooks_dir: PathBuf,
+    work_tree: PathBuf,
+    calls: RefCell<Vec<String>>,
+}
+
+impl Default for FakeBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FakeBackend {
+    /// A clean repository where commits succeed
+    pub fn new() -> Self {
+        Self {
+            is_repository: true,
+            status: String::new(),
+            commit_output: GitOutput::success(""),
+            logs: HashMap::new(),
+            tags: Vec::new(),
+            diffs: HashMap::new(),
+            files: HashMap::new(),
+            config: HashMap::new(),
+            git_files: HashMap::new(),
+            hooks_dir: PathBuf::from(".git/hooks"),
+            work_tree: PathBuf::from("."),
+            calls: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Behave as if run outside of any repository
+    pub fn outside_repository(mut self) -> Self {
+        self.is_repository = false;
+        self
+    }
+
+    /// Set the `git status --porcelain=v2 -z` output
+    pub fn with_status(mut self, status: impl Into<String>) -> Self {
+        self.status = status.into();
+        self
+    }
+
+    /// Set the output every commit returns
+    pub fn with_commit_output(mut self, output: GitOutput) -> Self {
+        self.commit_output = output;
+        self
+    }
+
+    /// Set the commits returned for a set of revision arguments (joined with spaces)
+    pub fn with_log(mut self, revisions: &str, commits: Vec<CommitInfo>) -> Self {
+        self.logs.insert(revisions.to_string(), commits);
+        self
+    }
+
+    /// Set the tags returned for any revision, most recently created first
+    pub fn with_tags(mut self, tags: &[&str]) -> Self {
+        self.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        self
+    }
+
+    /// Set the diff returned for a set of diff arguments (joined with spaces)
+    pub fn with_diff(mut self, args: &str, diff: impl Into<String>) -> Self {
+        self.diffs.insert(args.to_string(), diff.into());
+        self
+    }
+
+    /// Set the contents of a blob such as "HEAD:Cargo.toml"
+    pub fn with_file(mut self, object: &str, contents: impl Into<String>) -> Self {
+        self.files.insert(object.to_string(), contents.into());
+        self
+    }
+
+    /// Set a config value
+    pub fn with_config(mut self, key: &str, value: &str) -> Self {
+        self.config.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Set the contents of a state file in the git directory such as "MERGE_HEAD"
+    pub fn with_git_file(mut self, name: &str, contents: impl Into<String>) -> Self {
+        self.git_files.insert(name.to_string(), contents.into());
+        self
+    }
+
+    /// Set the hooks directory
+    pub fn with_hooks_dir(mut self, hooks_dir: impl Into<PathBuf>) -> Self {
+        self.hooks_dir = hooks_dir.into();
+        self
+    }
+
+    /// Set the top-level directory of the work tree
+    pub fn with_work_tree(mut self, work_tree: impl Into<PathBuf>) -> Self {
+        self.work_tree = work_tree.into();
+        self
+    }
+
+    /// Every call made so far, as "<operation> <args>"
+    pub fn calls(&self) -> Vec<String> {
+        self.calls.borrow().clone()
+    }
+
+    fn record(&self, operation: &str, args: &[&str]) {
+        let call = std::iter::once(operation)
+            .chain(args.iter().copied())
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.calls.borrow_mut().push(call);
+    }
+}
+
+impl GitBackend for FakeBackend {
+    fn is_repository(&self) -> Result<bool> {
+        self.record("is_repository", &[]);
+        Ok(self.is_repository)
+    }
+
+    fn status(&self) -> Result<String> {
+        self.record("status", &[]);
+        Ok(self.status.clone())
+    }
+
+    fn commit(&self, args: &[String]) -> Result<GitOutput> {
+        // The argv already starts with "commit"
+        let args: Vec<&str> = args.iter().skip(1).map(String::as_str).collect();
+        self.record("commit", &args);
+        Ok(self.commit_output.clone())
+    }
+
+    fn log(&self, revisions: &[&str]) -> Result<Vec<CommitInfo>> {
+        self.record("log", revisions);
+        Ok(self
+            .logs
+            .get(&revisions.join(" "))
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn tags(&self, revision: &str) -> Result<Vec<String>> {
+        self.record("tags", &[revision]);
+        Ok(self.tags.clone())
+    }
+
+    fn diff(&self, args: &[&str]) -> Result<String> {
+        self.record("diff", args);
+        Ok(self.diffs.get(&args.join(" ")).cloned().unwrap_or_default())
+    }
+
+    fn show(&self, object: &str) -> Result<Option<String>> {
+        self.record("show", &[object]);
+        Ok(self.files.get(object).cloned())
+    }
+
+    fn config_get(&self, key: &str) -> Result<Option<String>> {
+        self.record("config", &[key]);
+        Ok(self.config.get(key).cloned())
+    }
+
+    fn hooks_dir(&self) -> Result<PathBuf> {
+        self.record("hooks_dir", &[]);
+        Ok(self.hooks_dir.clone())
+    }
+
+    fn git_file(&self, name: &str) -> Result<Option<String>> {
+        self.record("git_file", &[name]);
+        Ok(self.git_files.get(name).cloned())
+    }
+
+    fn work_tree(&self) -> Result<PathBuf> {
+        self.record("work_tree", &[]);
+        Ok(self.work_tree.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hook_events() {
+        let start = |sid: &str, id: u32, hook: &str| {
+            format!(
+                r#"{{"event":"child_start","sid":"{sid}","child_id":{id},"child_class":"hook","hook_name":"{hook}","argv":[".git/hooks/{hook}"]}}"#
+            )
+        };
+        let exit = |sid: &str, id: u32, code: i32| {
+            format!(
+                r#"{{"event":"child_exit","sid":"{sid}","child_id":{id},"pid":42,"code":{code}}}"#
+            )
+        };
+        let version = r#"{"event":"version","sid":"top","evt":"3","exe":"2.39.5"}"#;
+
+        assert_eq!(ProcessBackend::parse_hook_events(""), HookOutcome::Unknown);
+        assert_eq!(
+            ProcessBackend::parse_hook_events(version),
+            HookOutcome::Passed
+        );
+
+        let events = [
+            version.to_string(),
+            start("top", 0, "pre-commit"),
+            exit("top", 0, 0),
+            start("top", 1, "commit-msg"),
+            exit("top", 1, 1),
+        ]
+        .join("\n");
+        assert_eq!(
+            ProcessBackend::parse_hook_events(&events),
+            HookOutcome::Failed("commit-msg".to_string())
+        );
+
+        // A git command run from inside a hook traces its own hooks under a nested session
+        let events = [
+            version.to_string(),
+            start("top", 0, "pre-commit"),
+            start("top/nested", 0, "pre-commit"),
+            exit("top/nested", 0, 1),
+            exit("top", 0, 0),
+        ]
+        .join("\n");
+        assert_eq!(
+            ProcessBackend::parse_hook_events(&events),
+            HookOutcome::Passed
+        );
+
+        // Non-hook children such as the editor don't count
+        let events = [
+            version.to_string(),
+            r#"{"event":"child_start","sid":"top","child_id":0,"child_class":"editor"}"#
+                .to_string(),
+            exit("top", 0, 1),
+        ]
+        .join("\n");
+        assert_eq!(
+            ProcessBackend::parse_hook_events(&events),
+            HookOutcome::Passed
+        );
+    }
+
+    #[test]
+    fn test_log_args() {
+        assert_eq!(
+            ProcessBackend::log_args(&["-n", "20", "main"]).unwrap(),
+            ["log", LOG_FORMAT, "-n", "20", "main", "--"]
+        );
+        // Paths after the user's own `--` keep a single separator
+        assert_eq!(
+            ProcessBackend::log_args(&["--", "src/"]).unwrap(),
+            ["log", LOG_FORMAT, "--", "src/"]
+        );
+        assert_eq!(
+            ProcessBackend::log_args(&["main", "--", "--oneline"]).unwrap(),
+            ["log", LOG_FORMAT, "main", "--", "--oneline"]
+        );
+
+        for option in [
+            "--oneline",
+            "--pretty=short",
+            "--format=%h",
+            "--graph",
+            "-p",
+        ] {
+            let error = ProcessBackend::log_args(&["-n", "5", option]).unwrap_err();
+            assert!(
+                error.to_string().contains(option),
+                "{option} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_log() {
+        let output = "aaaaaaaaaa\x1fbbbbbbbbbb\x1fAda\x1f1700000000\x1f:bug: fix login\x1f\x1e\n\
+                      bbbbbbbbbb\x1fcccccccccc dddddddddd\x1fAda\x1f1690000000\x1fMerge branch 'x'\x1f\x1e\n\
+                      cccccccccc\x1f\x1fGrace Hopper\x1f1680000000\x1finitial commit\x1fBREAKING CHANGE: none\n\n\x1e\n";
+
+        let commits = ProcessBackend::parse_log(output);
+        assert_eq!(commits.len(), 3);
+
+        assert_eq!(commits[0].sha, "aaaaaaaaaa");
+        assert_eq!(commits[0].short_sha(), "aaaaaaa");
+        assert_eq!(commits[0].subject, ":bug: fix login");
+        assert_eq!(commits[0].author, "Ada");
+        assert_eq!(commits[0].timestamp, 1_700_000_000);
+        assert!(!commits[0].is_merge());
+
+        assert_eq!(commits[1].parents.len(), 2);
+        assert!(commits[1].is_merge());
+
+        // Root commits have no parents
+        assert!(commits[2].parents.is_empty());
+        assert_eq!(commits[2].subject, "initial commit");
+        assert_eq!(commits[2].body, "BREAKING CHANGE: none");
+        assert_eq!(
+            commits[2].message(),
+            "initial commit\n\nBREAKING CHANGE: none"
+        );
+        assert_eq!(commits[0].body, "");
+        assert_eq!(commits[0].message(), ":bug: fix login");
+
+        assert!(ProcessBackend::parse_log("").is_empty());
+    }
+
+    #[test]
+    fn test_git_output() {
+        assert!(GitOutput::success("done").is_success());
+        assert!(!GitOutput::failure(1, "", "boom").is_success());
+    }
+
+    #[test]
+    fn test_fake_backend_records_calls() {
+        let backend = FakeBackend::new()
+            .with_status("M  src/lib.rs\n")
+            .with_config("core.commentChar", ";")
+            .with_diff("--cached", "diff --git a/src/lib.rs b/src/lib.rs\n");
+
+        assert!(backend.is_repository().unwrap());
+        assert_eq!(backend.status().unwrap(), "M  src/lib.rs\n");
+        assert_eq!(
+            backend.config_get("core.commentChar").unwrap(),
+            Some(";".to_string())
+        );
+        assert_eq!(backend.config_get("user.name").unwrap(), None);
+        assert!(
+            backend
+                .diff(&["--cached"])
+                .unwrap()
+                .starts_with("diff --git")
+        );
+        assert!(backend.log(&["HEAD~3..HEAD"]).unwrap().is_empty());
+        backend
+            .commit(&["commit".to_string(), "-m".to_string(), "msg".to_string()])
+            .unwrap();
+
+        assert_eq!(
+            backend.calls(),
+            vec![
+                "is_repository",
+                "status",
+                "config core.commentChar",
+                "config user.name",
+                "diff --cached",
+                "log HEAD~3..HEAD",
+                "commit -m msg",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fake_backend_canned_log() {
+        let commits = vec![CommitInfo {
+            sha: "abc".to_string(),
+            parents: vec![],
+            subject: ":tada: init".to_string(),
+            ..Default::default()
+        }];
+        let backend = FakeBackend::new().with_log("abc --not --all", commits.clone());
+
+        assert_eq!(backend.log(&["abc", "--not", "--all"]).unwrap(), commits);
+        assert!(backend.log(&["abc"]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_process_backend_repo_overrides_environment() {
+        let backend = ProcessBackend::new();
+        let command = backend.command(&["status"]);
+        assert_eq!(command.get_args().collect::<Vec<_>>(), vec!["status"]);
+        assert_eq!(command.get_envs().count(), 0);
+
+        let backend = ProcessBackend::in_repo("packages/api");
+        assert_eq!(backend.repo(), Some(Path::new("packages/api")));
+
+        let command = backend.command(&["status"]);
+        assert_eq!(
+            command.get_args().collect::<Vec<_>>(),
+            vec!["-C", "packages/api", "status"]
+        );
+        let removed: Vec<_> = command
+            .get_envs()
+            .filter(|(_, value)| value.is_none())
+            .map(|(key, _)| key.to_string_lossy().to_string())
+            .collect();
+        assert_eq!(removed.len(), REPOSITORY_ENV_VARS.len());
+        assert!(
+            REPOSITORY_ENV_VARS
+                .iter()
+                .all(|var| removed.contains(&var.to_string()))
+        );
+    }
+
+    #[test]
+    #[ignore = "requires git repository"]
+    fn test_process_backend_in_git_repo() {
+        let backend = ProcessBackend::new();
+        assert!(backend.is_repository().unwrap());
+        assert!(backend.status().is_ok());
+        assert!(backend.hooks_dir().is_ok());
+    }
+}
@@ -0,0 +1,552 @@
+use crate::{
+    commit::CommitInfo,
+    emoji::{EmojiLookup, SEMVER_MAP, SemverImpact},
+    lint::{CommitLinter, SubjectGitmoji},
+    release::ReleasePlan,
+};
+use anyhow::Result;
+use serde::Serialize;
+use std::fmt::Write;
+
+/// Changelog sections, in the order they are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangelogSection {
+    Breaking,
+    Features,
+    Fixes,
+    Security,
+    Performance,
+    Dependencies,
+    Docs,
+    Refactoring,
+    Tests,
+    Build,
+    Other,
+}
+
+impl ChangelogSection {
+    pub const ALL: [ChangelogSection; 11] = [
+        ChangelogSection::Breaking,
+        ChangelogSection::Features,
+        ChangelogSection::Fixes,
+        ChangelogSection::Security,
+        ChangelogSection::Performance,
+        ChangelogSection::Dependencies,
+        ChangelogSection::Docs,
+        ChangelogSection::Refactoring,
+        ChangelogSection::Tests,
+        ChangelogSection::Build,
+        ChangelogSection::Other,
+    ];
+
+    /// Heading of the section
+    pub fn title(self) -> &'static str {
+        match self {
+            ChangelogSection::Breaking => "Breaking Changes",
+            ChangelogSection::Features => "Features",
+            ChangelogSection::Fixes => "Fixes",
+            ChangelogSection::Security => "Security",
+            ChangelogSection::Performance => "Performance",
+            ChangelogSection::Dependencies => "Dependencies",
+            ChangelogSection::Docs => "Docs",
+            ChangelogSection::Refactoring => "Refactoring",
+            ChangelogSection::Tests => "Tests",
+            ChangelogSection::Build => "Build & CI",
+            ChangelogSection::Other => "Other",
+        }
+    }
+
+    /// The gitmoji decorating the heading, none for the catch-all section
+    fn heading_emoji(self) -> Option<&'static str> {
+        match self {
+            ChangelogSection::Other => None,
+            _ => EmojiLookup::code_to_unicode(self.codes()[0]),
+        }
+    }
+
+    /// The semver impact whose gitmojis all go to this section
+    fn semver(self) -> Option<SemverImpact> {
+        match self {
+            ChangelogSection::Breaking => Some(SemverImpact::Major),
+            ChangelogSection::Features => Some(SemverImpact::Minor),
+            _ => None,
+        }
+    }
+
+    /// The gitmojis filed under this section, the one for the heading first
+    /// Those of its semver impact come from `fixtures/gitmojis.json`, then the listed ones;
+    /// together the sections cover every gitmoji
+    pub fn codes(self) -> Vec<&'static str> {
+        let mut codes: Vec<&'static str> = SEMVER_MAP
+            .iter()
+            .filter(|(_, impact)| Some(**impact) == self.semver())
+            .map(|(code, _)| *code)
+            .collect();
+        codes.sort();
+        codes.extend(self.listed_codes());
+        codes
+    }
+
+    /// The gitmojis without a major or minor semver impact filed under this section
+    fn listed_codes(self) -> &'static [&'static str] {
+        match self {
+            ChangelogSection::Breaking => &[],
+            ChangelogSection::Features => &[
+                ":tada:",
+                ":lipstick:",
+                ":children_crossing:",
+                ":wheelchair:",
+                ":globe_with_meridians:",
+                ":iphone:",
+                ":dizzy:",
+                ":egg:",
+                ":triangular_flag_on_post:",
+                ":chart_with_upwards_trend:",
+                ":speech_balloon:",
+                ":necktie:",
+                ":passport_control:",
+                ":airplane:",
+                ":stethoscope:",
+                ":card_file_box:",
+                ":bento:",
+                ":mag:",
+                ":loud_sound:",
+                ":safety_vest:",
+                ":money_with_wings:",
+                ":seedling:",
+            ],
+            ChangelogSection::Fixes => &[
+                ":bug:",
+                ":ambulance:",
+                ":adhesive_bandage:",
+                ":pencil2:",
+                ":goal_net:",
+                ":alien:",
+                ":rotating_light:",
+                ":green_heart:",
+                ":rewind:",
+            ],
+            ChangelogSection::Security => &[":lock:", ":closed_lock_with_key:"],
+            ChangelogSection::Performance => &[":zap:", ":thread:"],
+            ChangelogSection::Dependencies => &[
+                ":arrow_up:",
+                ":package:",
+                ":heavy_plus_sign:",
+                ":heavy_minus_sign:",
+                ":arrow_down:",
+                ":pushpin:",
+            ],
+            ChangelogSection::Docs => &[
+                ":memo:",
+                ":bulb:",
+                ":page_facing_up:",
+                ":busts_in_silhouette:",
+            ],
+            ChangelogSection::Refactoring => &[
+                ":recycle:",
+                ":art:",
+                ":building_construction:",
+                ":truck:",
+                ":fire:",
+                ":coffin:",
+                ":wastebasket:",
+                ":label:",
+                ":mute:",
+            ],
+            ChangelogSection::Tests => &[
+                ":white_check_mark:",
+                ":test_tube:",
+                ":camera_flash:",
+                ":clown_face:",
+            ],
+            ChangelogSection::Build => &[
+                ":construction_worker:",
+                ":wrench:",
+                ":hammer:",
+                ":bricks:",
+                ":see_no_evil:",
+                ":technologist:",
+                ":rocket:",
+                ":bookmark:",
+            ],
+            ChangelogSection::Other => &[
+                ":construction:",
+                ":alembic:",
+                ":poop:",
+                ":beers:",
+                ":monocle_face:",
+                ":twisted_rightwards_arrows:",
+            ],
+        }
+    }
+
+    /// The section of a gitmoji; commits without one go to Other
+    pub fn for_code(code: Option<&str>) -> Self {
+        let Some(code) = code else {
+            return ChangelogSection::Other;
+        };
+        match EmojiLookup::semver(code) {
+            Some(SemverImpact::Major) => ChangelogSection::Breaking,
+            Some(SemverImpact::Minor) => ChangelogSection::Features,
+            _ => Self::ALL
+                .into_iter()
+                .find(|section| section.listed_codes().contains(&code))
+                .unwrap_or(ChangelogSection::Other),
+        }
+    }
+
+    /// The Keep a Changelog category of a commit in this section
+    fn keep_a_changelog_category(self, code: Option<&str>) -> &'static str {
+        match (self, code) {
+            (_, Some(":wastebasket:")) => "Deprecated",
+            (_, Some(":fire:" | ":coffin:" | ":heavy_minus_sign:" | ":mute:")) => "Removed",
+            (ChangelogSection::Features, _) | (_, Some(":heavy_plus_sign:")) => "Added",
+            (ChangelogSection::Fixes, _) => "Fixed",
+            (ChangelogSection::Security, _) => "Security",
+            _ => "Changed",
+        }
+    }
+}
+
+/// Keep a Changelog categories, in the order the format lists them
+const KEEP_A_CHANGELOG_CATEGORIES: [&str; 6] = [
+    "Added",
+    "Changed",
+    "Deprecated",
+    "Removed",
+    "Fixed",
+    "Security",
+];
+
+/// One commit in the changelog
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChangelogEntry {
+    pub sha: String,
+    /// The gitmoji code, None when the subject has no known gitmoji
+    pub code: Option<&'static str>,
+    /// The subject without its gitmoji
+    pub text: String,
+}
+
+impl ChangelogEntry {
+    fn short_sha(&self) -> &str {
+        &self.sha[..self.sha.len().min(7)]
+    }
+
+    /// The entry as a Markdown list item, with its gitmoji when it has one
+    fn to_markdown(&self) -> String {
+        match self.code.and_then(EmojiLookup::code_to_unicode) {
+            Some(emoji) => format!("- {emoji} {} ({})", self.text, self.short_sha()),
+            None => format!("- {} ({})", self.text, self.short_sha()),
+        }
+    }
+}
+
+/// The entries of one non-empty section
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChangelogGroup {
+    pub section: ChangelogSection,
+    pub title: &'static str,
+    pub entries: Vec<ChangelogEntry>,
+}
+
+/// Commits of a revision range grouped by gitmoji section
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Changelog {
+    /// Start of the range (exclusive), None for the whole history
+    pub from: Option<String>,
+    pub to: String,
+    pub sections: Vec<ChangelogGroup>,
+}
+
+impl Changelog {
+    /// Group commits, newest first as `git log` lists them, by the section of their gitmoji
+    /// Commits that declare a breaking change with a `!` marker or a `BREAKING CHANGE:`
+    /// trailer go to Breaking Changes whatever their gitmoji, as they count for `amoji bump`.
+    /// Merges and fixup!/squash!/amend! commits are left out
+    pub fn from_commits(commits: &[CommitInfo], from: Option<&str>, to: &str) -> Self {
+        let mut sections: Vec<ChangelogGroup> = ChangelogSection::ALL
+            .into_iter()
+            .map(|section| ChangelogGroup {
+                section,
+                title: section.title(),
+                entries: Vec::new(),
+            })
+            .collect();
+
+        for commit in commits {
+            if commit.is_merge() || CommitLinter::is_exempt(&commit.subject) {
+                continue;
+            }
+
+            let (code, text) = match SubjectGitmoji::parse(&commit.subject) {
+                SubjectGitmoji::Valid { code, text } => (Some(code), text),
+                _ => (None, commit.subject.trim()),
+            };
+            let section = match ReleasePlan::commit_impact(&commit.message()) {
+                Some(SemverImpact::Major) => ChangelogSection::Breaking,
+                _ => ChangelogSection::for_code(code),
+            };
+            sections
+                .iter_mut()
+                .find(|group| group.section == section)
+                .expect("every section has a group")
+                .entries
+                .push(ChangelogEntry {
+                    sha: commit.sha.clone(),
+                    code,
+                    text: text.to_string(),
+                });
+        }
+
+        sections.retain(|group| !group.entries.is_empty());
+        Self {
+            from: from.map(String::from),
+            to: to.to_string(),
+            sections,
+        }
+    }
+
+    /// Whether no commit made it into the changelog
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    /// The range as git would write it
+    fn range(&self) -> String {
+        match &self.from {
+            Some(from) => format!("{from}..{}", self.to),
+            None => self.to.clone(),
+        }
+    }
+
+    /// Markdown with one `###` heading per section
+    pub fn to_markdown(&self) -> String {
+        let mut output = format!("## {}\n", self.range());
+
+        for group in &self.sections {
+            match group.section.heading_emoji() {
+                Some(emoji) => writeln!(output, "\n### {emoji} {}\n", group.title),
+                None => writeln!(output, "\n### {}\n", group.title),
+            }
+            .unwrap();
+            for entry in &group.entries {
+                writeln!(output, "{}", entry.to_markdown()).unwrap();
+            }
+        }
+
+        output
+    }
+
+    /// The changelog as pretty-printed JSON
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// A Keep a Changelog document with one release for the range
+    /// The release is "Unreleased" when the range ends at HEAD, otherwise it is named after `to`
+    pub fn to_keep_a_changelog(&self) -> String {
+        let mut output = String::from(
+            "# Changelog\n\n\
+             All notable changes to this project will be documented in this file.\n\n\
+             The format is based on [Keep a Changelog](https://keepachangelog.com/en/1.1.0/).\n",
+        );
+
+        let release = if self.to == "HEAD" {
+            "Unreleased"
+        } else {
+            &self.to
+        };
+        writeln!(output, "\n## [{release}]").unwrap();
+
+        for category in KEEP_A_CHANGELOG_CATEGORIES {
+            let entries: Vec<(ChangelogSection, &ChangelogEntry)> = self
+                .sections
+                .iter()
+                .flat_map(|group| group.entries.iter().map(|entry| (group.section, entry)))
+                .filter(|(section, entry)| {
+                    section.keep_a_changelog_category(entry.code) == category
+                })
+                .collect();
+            if entries.is_empty() {
+                continue;
+            }
+
+            writeln!(output, "\n### {category}\n").unwrap();
+            for (section, entry) in entries {
+                let breaking = if section == ChangelogSection::Breaking {
+                    "**BREAKING:** "
+                } else {
+                    ""
+                };
+                writeln!(output, "- {breaking}{} ({})", entry.text, entry.short_sha()).unwrap();
+            }
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::emoji::EMOJI_MAP;
+
+    fn commit(sha: &str, subject: &str) -> CommitInfo {
+        CommitInfo {
+            sha: sha.to_string(),
+            parents: vec!["0000000".to_string()],
+            subject: subject.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn sample() -> Changelog {
+        let commits = vec![
+            commit("aaaaaaa1", ":sparkles: add stats command"),
+            commit("bbbbbbb2", "🐛 fix crash on empty log"),
+            commit("ccccccc3", ":boom: drop the v1 config format"),
+            commit("ddddddd4", ":arrow_up: bump serde"),
+            commit("eeeeeee5", "fixup! :sparkles: add stats command"),
+            commit("fffffff6", "tweak things"),
+            commit("ggggggg7", ":fire: remove legacy matcher"),
+            CommitInfo {
+                parents: vec!["1".to_string(), "2".to_string()],
+                ..commit("hhhhhhh8", "Merge branch 'topic'")
+            },
+        ];
+        Changelog::from_commits(&commits, Some("v1.0.0"), "HEAD")
+    }
+
+    #[test]
+    fn test_every_gitmoji_has_one_section() {
+        let mut codes: Vec<&str> = ChangelogSection::ALL
+            .iter()
+            .flat_map(|section| section.codes())
+            .collect();
+        for code in &codes {
+            assert!(
+                EMOJI_MAP.contains_key(code),
+                "{code} is not a known gitmoji"
+            );
+        }
+
+        // Major and minor gitmojis are filed by their semver impact, never by the lists
+        for section in ChangelogSection::ALL {
+            for code in section.listed_codes() {
+                assert!(
+                    EmojiLookup::semver(code) < Some(SemverImpact::Minor),
+                    "{code} is listed although its semver impact decides its section"
+                );
+            }
+        }
+        assert_eq!(ChangelogSection::Breaking.codes(), [":boom:"]);
+        assert_eq!(ChangelogSection::Features.codes()[0], ":sparkles:");
+
+        codes.sort();
+        let count = codes.len();
+        codes.dedup();
+        assert_eq!(codes.len(), count, "a gitmoji is listed in two sections");
+
+        for code in EMOJI_MAP.keys() {
+            assert!(codes.contains(code), "{code} has no changelog section");
+        }
+    }
+
+    #[test]
+    fn test_from_commits_groups_by_section() {
+        let changelog = sample();
+
+        let titles: Vec<&str> = changelog.sections.iter().map(|g| g.title).collect();
+        assert_eq!(
+            titles,
+            [
+                "Breaking Changes",
+                "Features",
+                "Fixes",
+                "Dependencies",
+                "Refactoring",
+                "Other"
+            ]
+        );
+
+        let fixes = &changelog.sections[2];
+        assert_eq!(fixes.entries[0].code, Some(":bug:"));
+        assert_eq!(fixes.entries[0].text, "fix crash on empty log");
+
+        // Commits without a gitmoji keep their whole subject
+        let other = changelog.sections.last().unwrap();
+        assert_eq!(other.entries[0].code, None);
+        assert_eq!(other.entries[0].text, "tweak things");
+
+        assert!(Changelog::from_commits(&[], None, "HEAD").is_empty());
+    }
+
+    #[test]
+    fn test_declared_breaking_changes_go_to_breaking() {
+        let mut trailer = commit("bbbbbbb2", ":recycle: rename config keys");
+        trailer.body = "BREAKING CHANGE: `max_len` is now `max-len`".to_string();
+        let commits = vec![
+            commit("aaaaaaa1", ":sparkles: feat(api)!: drop v1 endpoints"),
+            trailer,
+            commit("ccccccc3", ":sparkles: add stats command"),
+        ];
+        let changelog = Changelog::from_commits(&commits, None, "HEAD");
+
+        assert_eq!(changelog.sections[0].section, ChangelogSection::Breaking);
+        let breaking: Vec<&str> = changelog.sections[0]
+            .entries
+            .iter()
+            .map(|entry| entry.short_sha())
+            .collect();
+        assert_eq!(breaking, ["aaaaaaa", "bbbbbbb"]);
+        // The entry keeps its own gitmoji
+        assert_eq!(changelog.sections[0].entries[1].code, Some(":recycle:"));
+
+        assert_eq!(changelog.sections[1].section, ChangelogSection::Features);
+        assert_eq!(changelog.sections[1].entries.len(), 1);
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let markdown = sample().to_markdown();
+
+        assert!(markdown.starts_with("## v1.0.0..HEAD\n\n### 💥 Breaking Changes\n\n"));
+        assert!(markdown.contains("### ✨ Features\n\n- ✨ add stats command (aaaaaaa)\n"));
+        assert!(markdown.contains("### Other\n\n- tweak things (fffffff)\n"));
+        assert!(!markdown.contains("fixup!"));
+        assert!(!markdown.contains("Merge branch"));
+    }
+
+    #[test]
+    fn test_to_json() {
+        let json: serde_json::Value = serde_json::from_str(&sample().to_json().unwrap()).unwrap();
+
+        assert_eq!(json["from"], "v1.0.0");
+        assert_eq!(json["sections"][0]["section"], "breaking");
+        assert_eq!(json["sections"][0]["entries"][0]["code"], ":boom:");
+        assert_eq!(
+            json["sections"][5]["entries"][0]["code"],
+            serde_json::Value::Null
+        );
+    }
+
+    #[test]
+    fn test_to_keep_a_changelog() {
+        let output = sample().to_keep_a_changelog();
+
+        assert!(output.starts_with("# Changelog\n"));
+        assert!(output.contains("\n## [Unreleased]\n"));
+        assert!(output.contains("### Added\n\n- add stats command (aaaaaaa)\n"));
+        assert!(output.contains(
+            "### Changed\n\n- **BREAKING:** drop the v1 config format (ccccccc)\n- bump serde (ddddddd)\n- tweak things (fffffff)\n"
+        ));
+        assert!(output.contains("### Removed\n\n- remove legacy matcher (ggggggg)\n"));
+        assert!(output.contains("### Fixed\n\n- fix crash on empty log (bbbbbbb)\n"));
+        assert!(!output.contains("### Security"));
+
+        let release = Changelog::from_commits(&[commit("a", ":bug: x")], None, "v2.0.0");
+        assert!(release.to_keep_a_changelog().contains("\n## [v2.0.0]\n"));
+    }
+}
@@ -0,0 +1,75 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::Path;
+
+/// Per-repository settings read from `.amoji.json` at the top of the work tree
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RepoConfig {
+    pub paths: PathConfig,
+}
+
+/// Path patterns per category, replacing the built-in patterns of the categories that are set
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PathConfig {
+    pub docs: Option<Vec<String>>,
+    pub tests: Option<Vec<String>>,
+    pub ci: Option<Vec<String>>,
+    pub assets: Option<Vec<String>>,
+    pub license: Option<Vec<String>>,
+}
+
+impl RepoConfig {
+    /// Name of the config file, relative to the top of the work tree
+    pub const FILE_NAME: &'static str = ".amoji.json";
+
+    /// Load the config of the repository at `work_tree`, using defaults when there is none
+    pub fn load(work_tree: &Path) -> Result<Self> {
+        let path = work_tree.join(Self::FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::from_json(&contents).with_context(|| format!("Invalid config in {}", path.display()))
+    }
+
+    /// Parse a config from its JSON text
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_json() {
+        let config = RepoConfig::from_json(r#"{"paths": {"docs": ["handbook/**"]}}"#).unwrap();
+        assert_eq!(config.paths.docs, Some(vec!["handbook/**".to_string()]));
+        assert_eq!(config.paths.tests, None);
+
+        assert_eq!(RepoConfig::from_json("{}").unwrap(), RepoConfig::default());
+        assert!(RepoConfig::from_json(r#"{"paths": {"documentation": []}}"#).is_err());
+    }
+
+    #[test]
+    fn test_load_missing_file_uses_defaults() {
+        let dir = std::env::temp_dir().join(format!("amoji-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(RepoConfig::load(&dir).unwrap(), RepoConfig::default());
+
+        std::fs::write(
+            dir.join(RepoConfig::FILE_NAME),
+            r#"{"paths": {"ci": ["ci/**"]}}"#,
+        )
+        .unwrap();
+        let config = RepoConfig::load(&dir).unwrap();
+        assert_eq!(config.paths.ci, Some(vec!["ci/**".to_string()]));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,210 @@
+use super::{DiffSignal, content::Hunk};
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// How many public items a signal reason lists before summarizing the rest
+const MAX_ITEMS_IN_REASON: usize = 3;
+
+/// Directories whose `pub` items are not part of a crate's public API
+const NON_API_DIRS: [&str; 3] = ["tests", "benches", "examples"];
+
+/// Keyword matches a guessed breaking change is specific enough to replace
+const REFINED_BY_HUNKS: &[&str] = &[":sparkles:", ":recycle:"];
+
+static BREAKING_TRAILER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^BREAKING[ -]CHANGE:").unwrap());
+static CONVENTIONAL_BANG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*[A-Za-z]+(\([^)]*\))?!:").unwrap());
+static RUST_PUBLIC_ITEM: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^\s*pub\s+(?:(?:async|const|unsafe|extern(?:\s+\x22[^\x22]*\x22)?)\s+)*(fn|struct|enum|trait|type|union|mod|const|static)\s+([A-Za-z_][A-Za-z0-9_]*)",
+    )
+    .unwrap()
+});
+
+/// Detects changes that break users and deserve `:boom:`
+pub struct BreakingChange;
+
+impl BreakingChange {
+    /// The gitmoji for a major, breaking change
+    pub const CODE: &'static str = ":boom:";
+
+    /// A signal when the message declares a breaking change
+    /// Recognizes a `BREAKING CHANGE:` trailer and the conventional `type(scope)!:` marker
+    pub fn message_signal(message: &str) -> Option<DiffSignal> {
+        let subject = message.lines().next().unwrap_or_default();
+
+        let reason = if BREAKING_TRAILER.is_match(message) {
+            "BREAKING CHANGE trailer in the message"
+        } else if CONVENTIONAL_BANG.is_match(subject) {
+            "conventional commit `!` marker in the subject"
+        } else {
+            return None;
+        };
+        Some(DiffSignal::new(Self::CODE, reason).decisive())
+    }
+
+    /// A signal when the staged hunks remove or change public Rust items
+    /// Only a guess, so it replaces a feature or refactor keyword but not, say, a fix
+    pub fn hunk_signal(hunks: &[Hunk]) -> Option<DiffSignal> {
+        let items = Self::changed_public_items(hunks);
+        if items.is_empty() {
+            return None;
+        }
+
+        let mut listed: Vec<String> = items.iter().take(MAX_ITEMS_IN_REASON).cloned().collect();
+        if items.len() > MAX_ITEMS_IN_REASON {
+            listed.push(format!("{} more", items.len() - MAX_ITEMS_IN_REASON));
+        }
+        let reason = format!("public items removed or changed: {}", listed.join(", "));
+        Some(DiffSignal::new(Self::CODE, reason).refining(REFINED_BY_HUNKS))
+    }
+
+    /// Whether `pub` items in the file are part of the public API
+    /// Test, bench and example files and the binary entry point are not
+    fn is_api_file(path: &str) -> bool {
+        let mut dirs = path.split('/').rev().skip(1);
+        path.ends_with(".rs")
+            && path != "src/main.rs"
+            && !path.ends_with("/src/main.rs")
+            && !dirs.any(|dir| NON_API_DIRS.contains(&dir))
+    }
+
+    /// Public items whose declaration line was removed and not re-added unchanged elsewhere
+    fn changed_public_items(hunks: &[Hunk]) -> Vec<String> {
+        let squash = |line: &str| line.split_whitespace().collect::<String>();
+        let added: Vec<String> = hunks
+            .iter()
+            .filter(|h| Self::is_api_file(&h.path))
+            .flat_map(|h| h.added.iter().map(|line| squash(line)))
+            .collect();
+
+        let mut items = Vec::new();
+        for hunk in hunks.iter().filter(|h| Self::is_api_file(&h.path)) {
+            for line in &hunk.removed {
+                let Some(captures) = RUST_PUBLIC_ITEM.captures(line) else {
+                    continue;
+                };
+                // Moved or reformatted declarations are still there
+                if added.contains(&squash(line)) {
+                    continue;
+                }
+                let item = format!("pub {} {} ({})", &captures[1], &captures[2], hunk.path);
+                if !items.contains(&item) {
+                    items.push(item);
+                }
+            }
+        }
+
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hunk(path: &str, removed: &[&str], added: &[&str]) -> Hunk {
+        Hunk {
+            path: path.to_string(),
+            removed: removed.iter().map(|l| l.to_string()).collect(),
+            added: added.iter().map(|l| l.to_string()).collect(),
+            binary: false,
+        }
+    }
+
+    #[test]
+    fn test_message_signal() {
+        let signal = BreakingChange::message_signal(
+            "rework config loading\n\nBREAKING CHANGE: `load` now returns a Result\n",
+        )
+        .unwrap();
+        assert_eq!(signal.code, ":boom:");
+        assert_eq!(signal.reason, "BREAKING CHANGE trailer in the message");
+        assert!(signal.decisive);
+
+        let signal = BreakingChange::message_signal("feat(api)!: drop v1 endpoints").unwrap();
+        assert_eq!(
+            signal.reason,
+            "conventional commit `!` marker in the subject"
+        );
+        assert!(BreakingChange::message_signal("refactor!: rename crate").is_some());
+        assert!(BreakingChange::message_signal("BREAKING-CHANGE: x").is_some());
+
+        assert!(BreakingChange::message_signal("fix: handle ! in names").is_none());
+        assert!(BreakingChange::message_signal("docs: explain BREAKING CHANGE: usage").is_none());
+        assert!(BreakingChange::message_signal("").is_none());
+    }
+
+    #[test]
+    fn test_hunk_signal_removed_and_edited_items() {
+        let hunks = vec![
+            hunk(
+                "src/lib.rs",
+                &[
+                    "pub fn parse(input: &str) -> Config {",
+                    "pub struct Legacy;",
+                ],
+                &["pub fn parse(input: &str, strict: bool) -> Config {"],
+            ),
+            hunk(
+                "src/cli.rs",
+                &["pub(crate) fn helper() {}", "fn private() {}"],
+                &[],
+            ),
+        ];
+
+        let signal = BreakingChange::hunk_signal(&hunks).unwrap();
+        assert_eq!(signal.code, ":boom:");
+        assert_eq!(
+            signal.reason,
+            "public items removed or changed: pub fn parse (src/lib.rs), pub struct Legacy (src/lib.rs)"
+        );
+        // A guess from the diff never replaces a fix the author named
+        assert!(!signal.decisive);
+        assert!(signal.overrides(":sparkles:"));
+        assert!(!signal.overrides(":bug:"));
+    }
+
+    #[test]
+    fn test_hunk_signal_ignores_non_api_files() {
+        let hunks = vec![
+            hunk("tests/common.rs", &["pub fn helper(x: u32) {"], &[]),
+            hunk("benches/parse.rs", &["pub fn bench_parse() {"], &[]),
+            hunk("examples/demo.rs", &["pub struct Demo;"], &[]),
+            hunk("crates/core/tests/it.rs", &["pub fn fixture() {"], &[]),
+            hunk("src/main.rs", &["pub fn run() {"], &[]),
+        ];
+        assert!(BreakingChange::hunk_signal(&hunks).is_none());
+
+        let hunks = vec![hunk("src/tests.rs", &["pub fn kept() {"], &[])];
+        assert!(BreakingChange::hunk_signal(&hunks).is_some());
+    }
+
+    #[test]
+    fn test_hunk_signal_ignores_moves_and_additions() {
+        let hunks = vec![
+            hunk("src/a.rs", &["pub fn moved() {"], &[]),
+            hunk(
+                "src/b.rs",
+                &[],
+                &["pub  fn moved() {", "pub fn new_item() {}"],
+            ),
+            hunk("src/main.py", &["pub fn not_rust() {"], &[]),
+        ];
+        assert!(BreakingChange::hunk_signal(&hunks).is_none());
+
+        let hunks = vec![hunk(
+            "src/lib.rs",
+            &[
+                "pub async unsafe fn a() {}",
+                "pub const B: u8 = 1;",
+                "pub trait C {}",
+                "pub enum D {}",
+            ],
+            &[],
+        )];
+        let signal = BreakingChange::hunk_signal(&hunks).unwrap();
+        assert!(signal.reason.ends_with(", 1 more"));
+    }
+}
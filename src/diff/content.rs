@@ -0,0 +1,535 @@
+use super::DiffSignal;
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Largest edit distance between two words for the change to count as a typo fix
+const MAX_TYPO_DISTANCE: usize = 2;
+
+/// Shortest word changed in code that can count as a typo fix
+/// Shorter words are often different identifiers, e.g. `max` and `min`
+const MIN_CODE_TYPO_LEN: usize = 4;
+
+static LOG_STATEMENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^\s*(?:(?:log|tracing)::)?(?:e?println!|e?print!|dbg!|trace!|debug!|info!|warn!|error!|console\.(?:log|debug|info|warn|error|trace)\(|(?:self\.|this\.)?(?:logger|log|logging)\.(?:trace|debug|info|warn|warning|error|fatal|critical|exception|log)\(|print\(|System\.(?:out|err)\.print|fmt\.Print|log\.Print|Log\.[dviwe]\()",
+    )
+    .unwrap()
+});
+static WORD_OR_SYMBOL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\w+|\S").unwrap());
+
+/// Narrow kinds of change that have their own gitmoji
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentKind {
+    /// A single misspelled word was corrected
+    Typo,
+    /// Only whitespace or line breaks changed
+    Formatting,
+    /// Only comments or doc comments changed
+    Comments,
+    /// Only log statements were added
+    LogsAdded,
+    /// Only log statements were removed
+    LogsRemoved,
+}
+
+impl ContentKind {
+    /// All kinds, most specific first
+    pub const ALL: [ContentKind; 5] = [
+        ContentKind::Typo,
+        ContentKind::Formatting,
+        ContentKind::Comments,
+        ContentKind::LogsAdded,
+        ContentKind::LogsRemoved,
+    ];
+
+    /// The gitmoji for this kind of change
+    pub fn code(self) -> &'static str {
+        match self {
+            ContentKind::Typo => ":pencil2:",
+            ContentKind::Formatting => ":art:",
+            ContentKind::Comments => ":bulb:",
+            ContentKind::LogsAdded => ":loud_sound:",
+            ContentKind::LogsRemoved => ":mute:",
+        }
+    }
+
+    /// Message codes this kind is specific enough to replace
+    fn refines(self) -> &'static [&'static str] {
+        match self {
+            ContentKind::Typo => &[],
+            ContentKind::Formatting => &[":lipstick:"],
+            ContentKind::Comments => &[":memo:"],
+            ContentKind::LogsAdded | ContentKind::LogsRemoved => &[],
+        }
+    }
+}
+
+/// The changed lines of one hunk in a file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Hunk {
+    pub path: String,
+    pub removed: Vec<String>,
+    pub added: Vec<String>,
+    /// A binary file change, which has no lines to compare
+    pub binary: bool,
+}
+
+/// Word-level analysis of the staged hunks
+pub struct ContentAnalyzer;
+
+impl ContentAnalyzer {
+    /// Parse the output of `git diff --cached -U0` into hunks
+    /// Each binary file becomes a single hunk marked as binary
+    pub fn parse_hunks(diff: &str) -> Vec<Hunk> {
+        let mut hunks: Vec<Hunk> = Vec::new();
+        let mut path = String::new();
+        // Changed lines can look like headers ("--- x" removes "-- x"), so track where we are
+        let mut in_header = true;
+
+        for line in diff.lines() {
+            if line.starts_with("diff --git ") {
+                in_header = true;
+            } else if line.starts_with("@@") {
+                in_header = false;
+                hunks.push(Hunk {
+                    path: path.clone(),
+                    ..Hunk::default()
+                });
+            } else if in_header {
+                if line.starts_with("Binary files ") || line == "GIT binary patch" {
+                    hunks.push(Hunk {
+                        path: path.clone(),
+                        binary: true,
+                        ..Hunk::default()
+                    });
+                }
+                if let Some(new_path) = line.strip_prefix("+++ ") {
+                    path = new_path.strip_prefix("b/").unwrap_or(new_path).to_string();
+                }
+            } else if let Some(hunk) = hunks.last_mut() {
+                if let Some(added) = line.strip_prefix('+') {
+                    hunk.added.push(added.to_string());
+                } else if let Some(removed) = line.strip_prefix('-') {
+                    hunk.removed.push(removed.to_string());
+                }
+            }
+        }
+
+        hunks
+    }
+
+    /// Every kind that describes all of the hunks, most specific first
+    /// Binary changes can't be analyzed by content, so any of them rules out every kind
+    pub fn classify(hunks: &[Hunk]) -> Vec<ContentKind> {
+        if hunks.is_empty() || hunks.iter().any(|h| h.binary) {
+            return Vec::new();
+        }
+
+        ContentKind::ALL
+            .into_iter()
+            .filter(|kind| match kind {
+                ContentKind::Typo => Self::single_word_change(hunks).is_some(),
+                ContentKind::Formatting => hunks.iter().all(Self::is_whitespace_only),
+                ContentKind::Comments => hunks.iter().all(Self::is_comments_only),
+                ContentKind::LogsAdded => hunks
+                    .iter()
+                    .all(|h| h.removed.is_empty() && Self::is_logs_only(&h.added)),
+                ContentKind::LogsRemoved => hunks
+                    .iter()
+                    .all(|h| h.added.is_empty() && Self::is_logs_only(&h.removed)),
+            })
+            .collect()
+    }
+
+    /// Signals for the staged hunks, most specific first
+    pub fn signals(hunks: &[Hunk]) -> Vec<DiffSignal> {
+        Self::classify(hunks)
+            .into_iter()
+            .map(|kind| {
+                let reason = match kind {
+                    ContentKind::Typo => {
+                        let (old, new) = Self::single_word_change(hunks).unwrap_or_default();
+                        format!("a single word changed: {old} → {new}")
+                    }
+                    ContentKind::Formatting => "only whitespace changed".to_string(),
+                    ContentKind::Comments => "only comments changed".to_string(),
+                    ContentKind::LogsAdded => "only log statements added".to_string(),
+                    ContentKind::LogsRemoved => "only log statements removed".to_string(),
+                };
+                DiffSignal::new(kind.code(), reason).refining(kind.refines())
+            })
+            .collect()
+    }
+
+    fn is_whitespace_only(hunk: &Hunk) -> bool {
+        let squash = |lines: &[String]| -> String {
+            lines.iter().flat_map(|l| l.split_whitespace()).collect()
+        };
+        squash(&hunk.removed) == squash(&hunk.added)
+    }
+
+    fn is_comments_only(hunk: &Hunk) -> bool {
+        let Some(markers) = comment_markers(&hunk.path) else {
+            return false;
+        };
+        hunk.removed
+            .iter()
+            .chain(&hunk.added)
+            .map(|line| line.trim())
+            .all(|line| line.is_empty() || markers.iter().any(|m| line.starts_with(m)))
+            && !Self::is_whitespace_only(hunk)
+    }
+
+    fn is_logs_only(lines: &[String]) -> bool {
+        lines.iter().any(|line| !line.trim().is_empty())
+            && lines
+                .iter()
+                .all(|line| line.trim().is_empty() || LOG_STATEMENT.is_match(line))
+    }
+
+    /// The one word that differs across all hunks, when it looks like a spelling fix
+    /// In a comment or string literal any such word counts; elsewhere it has to be
+    /// `MIN_CODE_TYPO_LEN` letters long, and the old or the new spelling must not be used
+    /// anywhere else in the hunk, as then the change picks another identifier
+    fn single_word_change(hunks: &[Hunk]) -> Option<(String, String)> {
+        let mut change = None;
+
+        for hunk in hunks {
+            if hunk.removed.len() != hunk.added.len() {
+                return None;
+            }
+            for (old, new) in hunk.removed.iter().zip(&hunk.added) {
+                let old_tokens: Vec<_> = WORD_OR_SYMBOL.find_iter(old).collect();
+                let new_tokens: Vec<_> = WORD_OR_SYMBOL.find_iter(new).collect();
+                if old_tokens.len() != new_tokens.len() {
+                    return None;
+                }
+
+                for (a, b) in old_tokens.into_iter().zip(new_tokens) {
+                    if a.as_str() == b.as_str() {
+                        continue;
+                    }
+                    if change.is_some() {
+                        return None;
+                    }
+                    change = Some((hunk, a.as_str(), b.as_str(), is_prose(hunk, new, b.start())));
+                }
+            }
+        }
+
+        let (hunk, old, new, prose) = change?;
+        let is_typo = old.chars().all(char::is_alphabetic)
+            && new.chars().all(char::is_alphabetic)
+            && edit_distance(&old.to_lowercase(), &new.to_lowercase()) <= MAX_TYPO_DISTANCE
+            && old.to_lowercase() != new.to_lowercase()
+            && (prose
+                || old.chars().count().min(new.chars().count()) >= MIN_CODE_TYPO_LEN
+                    && (!Self::used_elsewhere(hunk, old) || !Self::used_elsewhere(hunk, new)));
+        is_typo.then(|| (old.to_string(), new.to_string()))
+    }
+
+    /// Whether a word appears in the hunk besides the one changed occurrence
+    fn used_elsewhere(hunk: &Hunk, word: &str) -> bool {
+        hunk.removed
+            .iter()
+            .chain(&hunk.added)
+            .flat_map(|line| WORD_OR_SYMBOL.find_iter(line))
+            .filter(|token| token.as_str() == word)
+            .count()
+            > 1
+    }
+}
+
+/// Line prefixes that start a comment in the language of `path`
+fn comment_markers(path: &str) -> Option<&'static [&'static str]> {
+    let extension = path.rsplit_once('.')?.1;
+    match extension {
+        "rs" | "c" | "h" | "cc" | "cpp" | "hpp" | "java" | "js" | "jsx" | "ts" | "tsx" | "go"
+        | "swift" | "kt" | "kts" | "cs" | "scala" | "dart" | "php" => {
+            Some(&["//", "/*", "*", "*/"])
+        }
+        "py" | "rb" | "sh" | "bash" | "zsh" | "yml" | "yaml" | "toml" | "pl" | "r" | "ex"
+        | "exs" => Some(&["#"]),
+        "sql" | "lua" | "hs" => Some(&["--"]),
+        "html" | "xml" | "vue" | "svelte" => Some(&["<!--", "-->"]),
+        _ => None,
+    }
+}
+
+/// Whether the text at byte `offset` of a line of `hunk` is inside a comment or a string
+/// literal; comments are only recognized in languages `comment_markers` knows
+fn is_prose(hunk: &Hunk, line: &str, offset: usize) -> bool {
+    let markers = comment_markers(&hunk.path).unwrap_or_default();
+    if markers.iter().any(|m| line.trim_start().starts_with(m)) {
+        return true;
+    }
+
+    // Rust uses single quotes for chars and lifetimes, not strings
+    let quotes: &[char] = if hunk.path.ends_with(".rs") {
+        &['"']
+    } else {
+        &['"', '\'', '`']
+    };
+    let mut open_quote = None;
+    let mut escaped = false;
+    for (i, c) in line[..offset].char_indices() {
+        match open_quote {
+            Some(quote) if !escaped && c == quote => open_quote = None,
+            Some(_) => escaped = !escaped && c == '\\',
+            None if quotes.contains(&c) => open_quote = Some(c),
+            None if markers
+                .iter()
+                .any(|m| !matches!(*m, "*" | "*/" | "-->") && line[i..].starts_with(m)) =>
+            {
+                return true;
+            }
+            None => {}
+        }
+    }
+    open_quote.is_some()
+}
+
+/// Optimal string alignment distance: insertions, deletions, substitutions and swaps of
+/// adjacent characters each cost one
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hunk(path: &str, removed: &[&str], added: &[&str]) -> Hunk {
+        Hunk {
+            path: path.to_string(),
+            removed: removed.iter().map(|l| l.to_string()).collect(),
+            added: added.iter().map(|l| l.to_string()).collect(),
+            binary: false,
+        }
+    }
+
+    #[test]
+    fn test_parse_hunks() {
+        let diff = "diff --git a/src/lib.rs b/src/lib.rs\n\
+                    index 1111111..2222222 100644\n\
+                    --- a/src/lib.rs\n\
+                    +++ b/src/lib.rs\n\
+                    @@ -3 +3 @@ fn main() {\n\
+                    -    let x = 1;\n\
+                    +    let x = 2;\n\
+                    @@ -10,0 +11,2 @@\n\
+                    +// one\n\
+                    +// two\n\
+                    diff --git a/schema.sql b/schema.sql\n\
+                    --- a/schema.sql\n\
+                    +++ b/schema.sql\n\
+                    @@ -1 +0,0 @@\n\
+                    --- drop me\n";
+
+        let hunks = ContentAnalyzer::parse_hunks(diff);
+        assert_eq!(
+            hunks,
+            vec![
+                hunk("src/lib.rs", &["    let x = 1;"], &["    let x = 2;"]),
+                hunk("src/lib.rs", &[], &["// one", "// two"]),
+                hunk("schema.sql", &["-- drop me"], &[]),
+            ]
+        );
+
+        let binary =
+            "diff --git a/logo.png b/logo.png\nBinary files a/logo.png and b/logo.png differ\n";
+        let hunks = ContentAnalyzer::parse_hunks(binary);
+        assert_eq!(hunks.len(), 1);
+        assert!(hunks[0].binary);
+        assert!(ContentAnalyzer::classify(&hunks).is_empty());
+    }
+
+    #[test]
+    fn test_typo() {
+        let hunks = vec![hunk(
+            "README.md",
+            &["Run the programm to recieve updates."],
+            &["Run the programm to receive updates."],
+        )];
+        assert_eq!(ContentAnalyzer::classify(&hunks), vec![ContentKind::Typo]);
+
+        // Renaming an identifier is not a spelling fix
+        let hunks = vec![hunk("src/lib.rs", &["let count = 1;"], &["let total = 1;"])];
+        assert!(ContentAnalyzer::classify(&hunks).is_empty());
+
+        // Two changed words are more than a typo
+        let hunks = vec![
+            hunk("a.md", &["teh cat"], &["the cat"]),
+            hunk("b.md", &["recieve"], &["receive"]),
+        ];
+        assert!(!ContentAnalyzer::classify(&hunks).contains(&ContentKind::Typo));
+    }
+
+    #[test]
+    fn test_typo_in_code() {
+        // Short words in code are more likely other identifiers than misspellings
+        let hunks = vec![hunk("src/lib.rs", &["a.max(b)"], &["a.min(b)"])];
+        assert!(ContentAnalyzer::classify(&hunks).is_empty());
+
+        // Inside a string literal or a trailing comment any word counts
+        let hunks = vec![hunk(
+            "src/main.rs",
+            &["    println!(\"teh end\");"],
+            &["    println!(\"the end\");"],
+        )];
+        assert_eq!(ContentAnalyzer::classify(&hunks), vec![ContentKind::Typo]);
+        let hunks = vec![hunk("app.py", &["x = 1  # teh x"], &["x = 1  # the x"])];
+        assert_eq!(ContentAnalyzer::classify(&hunks), vec![ContentKind::Typo]);
+
+        // A longer word in code is a typo fix unless both spellings are used in the hunk
+        let hunks = vec![hunk(
+            "src/lib.rs",
+            &["let lenght = items.len();"],
+            &["let length = items.len();"],
+        )];
+        assert_eq!(ContentAnalyzer::classify(&hunks), vec![ContentKind::Typo]);
+        let hunks = vec![hunk(
+            "src/lib.rs",
+            &["let cells = rows * cols;", "grid(rows, rows)"],
+            &["let cells = rows * cols;", "grid(rows, cols)"],
+        )];
+        assert!(ContentAnalyzer::classify(&hunks).is_empty());
+    }
+
+    #[test]
+    fn test_typo_in_comment() {
+        let hunks = vec![hunk(
+            "src/lib.rs",
+            &["// Retrun the value"],
+            &["// Return the value"],
+        )];
+        assert_eq!(
+            ContentAnalyzer::classify(&hunks),
+            vec![ContentKind::Typo, ContentKind::Comments]
+        );
+    }
+
+    #[test]
+    fn test_formatting() {
+        let hunks = vec![hunk(
+            "src/lib.rs",
+            &["fn main(){", "let x=foo( 1,2 );"],
+            &["fn main() {", "    let x=foo(1, 2);"],
+        )];
+        assert_eq!(
+            ContentAnalyzer::classify(&hunks),
+            vec![ContentKind::Formatting]
+        );
+    }
+
+    #[test]
+    fn test_comments() {
+        let hunks = vec![
+            hunk(
+                "src/lib.rs",
+                &["/// Old docs"],
+                &["/// New docs", "/// More"],
+            ),
+            hunk("scripts/run.py", &[], &["# explain the retry loop", ""]),
+        ];
+        assert_eq!(
+            ContentAnalyzer::classify(&hunks),
+            vec![ContentKind::Comments]
+        );
+
+        // Unknown languages can't be checked for comments
+        let hunks = vec![hunk("notes.txt", &[], &["// not code"])];
+        assert!(ContentAnalyzer::classify(&hunks).is_empty());
+
+        let hunks = vec![hunk("src/lib.rs", &[], &["// note", "let x = 1;"])];
+        assert!(ContentAnalyzer::classify(&hunks).is_empty());
+    }
+
+    #[test]
+    fn test_logs() {
+        let hunks = vec![
+            hunk(
+                "src/main.rs",
+                &[],
+                &[
+                    "    tracing::info!(\"starting\");",
+                    "    debug!(\"x = {x}\");",
+                ],
+            ),
+            hunk("web/app.ts", &[], &["  console.log(user);"]),
+            hunk(
+                "app/job.py",
+                &[],
+                &["        self.logger.warning('retrying')"],
+            ),
+        ];
+        assert_eq!(
+            ContentAnalyzer::classify(&hunks),
+            vec![ContentKind::LogsAdded]
+        );
+
+        let hunks = vec![hunk(
+            "src/main.rs",
+            &["    eprintln!(\"debug: {state:?}\");"],
+            &[],
+        )];
+        assert_eq!(
+            ContentAnalyzer::classify(&hunks),
+            vec![ContentKind::LogsRemoved]
+        );
+
+        let hunks = vec![hunk(
+            "src/main.rs",
+            &[],
+            &["    info!(\"x\");", "    run();"],
+        )];
+        assert!(ContentAnalyzer::classify(&hunks).is_empty());
+    }
+
+    #[test]
+    fn test_signals() {
+        let diff =
+            "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-// Retrun it\n+// Return it\n";
+
+        let signals = ContentAnalyzer::signals(&ContentAnalyzer::parse_hunks(diff));
+        assert_eq!(signals.len(), 2);
+        assert_eq!(signals[0].code, ":pencil2:");
+        assert_eq!(signals[0].reason, "a single word changed: Retrun → Return");
+        assert_eq!(signals[1].code, ":bulb:");
+        assert_eq!(signals[1].reason, "only comments changed");
+
+        assert!(ContentAnalyzer::signals(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("receive", "receive"), 0);
+        assert_eq!(edit_distance("recieve", "receive"), 1);
+        assert_eq!(edit_distance("fxi", "fix"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+}
@@ -0,0 +1,798 @@
+use super::DiffSignal;
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde_json::Value;
+use std::{cmp::Ordering, collections::BTreeMap, fmt, sync::LazyLock};
+
+/// Message codes a dependency signal is specific enough to replace
+const DEPENDENCY_CODES: &[&str] = &[
+    ":package:",
+    ":arrow_up:",
+    ":arrow_down:",
+    ":heavy_plus_sign:",
+    ":heavy_minus_sign:",
+    ":pushpin:",
+];
+
+/// How many dependency names a signal reason lists before summarizing the rest
+const MAX_NAMES_IN_REASON: usize = 3;
+
+static VERSION_NUMBER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\d+(\.\d+)*").unwrap());
+static QUOTED_STRING: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#""([^"]*)"|'([^']*)'"#).unwrap());
+static PEP508_NAME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*([A-Za-z0-9][A-Za-z0-9._-]*)\s*(\[[^\]]*\])?").unwrap());
+
+/// Dependency manifests and lockfiles amoji understands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestKind {
+    CargoToml,
+    CargoLock,
+    PackageJson,
+    PackageLock,
+    Pyproject,
+    /// `poetry.lock` and `uv.lock`, which share the `[[package]]` layout
+    PythonLock,
+}
+
+impl ManifestKind {
+    /// Detect the manifest kind from a repository-relative path
+    pub fn detect(path: &str) -> Option<Self> {
+        match path.rsplit('/').next()? {
+            "Cargo.toml" => Some(Self::CargoToml),
+            "Cargo.lock" => Some(Self::CargoLock),
+            "package.json" => Some(Self::PackageJson),
+            "package-lock.json" => Some(Self::PackageLock),
+            "pyproject.toml" => Some(Self::Pyproject),
+            "poetry.lock" | "uv.lock" => Some(Self::PythonLock),
+            _ => None,
+        }
+    }
+
+    /// Lockfiles record resolved versions rather than requirements
+    pub fn is_lockfile(self) -> bool {
+        matches!(self, Self::CargoLock | Self::PackageLock | Self::PythonLock)
+    }
+
+    /// Parse the dependencies of a manifest into name -> requirement (or locked version)
+    pub fn parse(self, contents: &str) -> Result<BTreeMap<String, String>> {
+        match self {
+            Self::CargoToml => Ok(parse_cargo_toml(contents)),
+            Self::PackageJson => parse_package_json(contents),
+            Self::PackageLock => parse_package_lock(contents),
+            Self::Pyproject => Ok(parse_pyproject(contents)),
+            Self::CargoLock | Self::PythonLock => Ok(parse_lock_packages(contents)),
+        }
+    }
+
+    /// Whether a requirement allows exactly one version
+    fn is_pinned(self, requirement: &str) -> bool {
+        let requirement = requirement.trim();
+        match self {
+            Self::CargoToml => requirement.starts_with('=') && !requirement.contains(','),
+            // npm and Poetry treat a bare version as an exact requirement
+            Self::PackageJson => is_bare_version(requirement),
+            Self::Pyproject => {
+                (requirement.starts_with("==") && !requirement.contains(','))
+                    || is_bare_version(requirement)
+            }
+            Self::CargoLock | Self::PackageLock | Self::PythonLock => false,
+        }
+    }
+}
+
+/// One dependency that differs between the committed and the staged manifest
+#[derive(Debug, Clone, PartialEq)]
+pub enum DependencyChange {
+    Added {
+        name: String,
+        version: String,
+    },
+    Removed {
+        name: String,
+    },
+    Upgraded {
+        name: String,
+        from: String,
+        to: String,
+    },
+    Downgraded {
+        name: String,
+        from: String,
+        to: String,
+    },
+    Pinned {
+        name: String,
+        version: String,
+    },
+}
+
+impl DependencyChange {
+    /// The gitmoji for this kind of change
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Added { .. } => ":heavy_plus_sign:",
+            Self::Removed { .. } => ":heavy_minus_sign:",
+            Self::Upgraded { .. } => ":arrow_up:",
+            Self::Downgraded { .. } => ":arrow_down:",
+            Self::Pinned { .. } => ":pushpin:",
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Added { name, .. }
+            | Self::Removed { name }
+            | Self::Upgraded { name, .. }
+            | Self::Downgraded { name, .. }
+            | Self::Pinned { name, .. } => name,
+        }
+    }
+
+    fn verb(&self) -> &'static str {
+        match self {
+            Self::Added { .. } => "added",
+            Self::Removed { .. } => "removed",
+            Self::Upgraded { .. } => "upgraded",
+            Self::Downgraded { .. } => "downgraded",
+            Self::Pinned { .. } => "pinned",
+        }
+    }
+}
+
+impl fmt::Display for DependencyChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Added { name, version } | Self::Pinned { name, version }
+                if !version.is_empty() =>
+            {
+                write!(f, "{name} {version}")
+            }
+            Self::Upgraded { name, from, to } | Self::Downgraded { name, from, to } => {
+                write!(f, "{name} {from} → {to}")
+            }
+            _ => write!(f, "{}", self.name()),
+        }
+    }
+}
+
+/// Compares the committed and staged versions of dependency manifests
+pub struct ManifestDiff;
+
+impl ManifestDiff {
+    /// List the dependency changes between two versions of a manifest
+    /// `None` stands for a manifest that doesn't exist on that side
+    pub fn compare(
+        kind: ManifestKind,
+        before: Option<&str>,
+        after: Option<&str>,
+    ) -> Result<Vec<DependencyChange>> {
+        let before = before
+            .map(|c| kind.parse(c))
+            .transpose()?
+            .unwrap_or_default();
+        let after = after
+            .map(|c| kind.parse(c))
+            .transpose()?
+            .unwrap_or_default();
+
+        let mut changes = Vec::new();
+        for (name, old) in &before {
+            match after.get(name) {
+                None => changes.push(DependencyChange::Removed { name: name.clone() }),
+                Some(new) if new != old => {
+                    changes.extend(Self::compare_versions(kind, name, old, new))
+                }
+                Some(_) => {}
+            }
+        }
+        for (name, new) in &after {
+            if !before.contains_key(name) {
+                changes.push(DependencyChange::Added {
+                    name: name.clone(),
+                    version: new.clone(),
+                });
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// Turn the changes of all manifests into signals, most frequent kind first
+    /// Lockfiles are only consulted when no manifest changed, since adding or bumping a
+    /// dependency also churns its transitive dependencies there
+    pub fn signals(changes: &[(ManifestKind, DependencyChange)]) -> Vec<DiffSignal> {
+        let from_manifests = changes.iter().any(|(kind, _)| !kind.is_lockfile());
+
+        let mut by_code: Vec<(&'static str, Vec<&DependencyChange>)> = Vec::new();
+        for (kind, change) in changes {
+            if from_manifests && kind.is_lockfile() {
+                continue;
+            }
+            match by_code.iter_mut().find(|(code, _)| *code == change.code()) {
+                Some((_, group)) if group.iter().any(|c| c.name() == change.name()) => {}
+                Some((_, group)) => group.push(change),
+                None => by_code.push((change.code(), vec![change])),
+            }
+        }
+
+        // Stable sort keeps the first-seen order between equally frequent kinds
+        by_code.sort_by_key(|(_, group)| std::cmp::Reverse(group.len()));
+        by_code
+            .into_iter()
+            .map(|(code, group)| {
+                let noun = if group.len() == 1 {
+                    "dependency"
+                } else {
+                    "dependencies"
+                };
+                let mut names: Vec<String> = group
+                    .iter()
+                    .take(MAX_NAMES_IN_REASON)
+                    .map(ToString::to_string)
+                    .collect();
+                if group.len() > MAX_NAMES_IN_REASON {
+                    names.push(format!("{} more", group.len() - MAX_NAMES_IN_REASON));
+                }
+                let reason = format!("{} {noun} {}", group[0].verb(), names.join(", "));
+                DiffSignal::new(code, reason).refining(DEPENDENCY_CODES)
+            })
+            .collect()
+    }
+
+    fn compare_versions(
+        kind: ManifestKind,
+        name: &str,
+        old: &str,
+        new: &str,
+    ) -> Option<DependencyChange> {
+        if kind.is_pinned(new) && !kind.is_pinned(old) {
+            return Some(DependencyChange::Pinned {
+                name: name.to_string(),
+                version: new.to_string(),
+            });
+        }
+
+        let (from, to) = (old.to_string(), new.to_string());
+        let name = name.to_string();
+        match compare_version_numbers(old, new)? {
+            Ordering::Less => Some(DependencyChange::Upgraded { name, from, to }),
+            Ordering::Greater => Some(DependencyChange::Downgraded { name, from, to }),
+            // Same version with different options, e.g. added features
+            Ordering::Equal => None,
+        }
+    }
+}
+
+/// Compare the first version number in two requirements component by component
+fn compare_version_numbers(old: &str, new: &str) -> Option<Ordering> {
+    let parse = |requirement: &str| -> Option<Vec<u64>> {
+        let version = VERSION_NUMBER.find(requirement)?;
+        version
+            .as_str()
+            .split('.')
+            .map(|n| n.parse().ok())
+            .collect()
+    };
+    let (mut old, mut new) = (parse(old)?, parse(new)?);
+
+    let len = old.len().max(new.len());
+    old.resize(len, 0);
+    new.resize(len, 0);
+    Some(old.cmp(&new))
+}
+
+fn is_bare_version(requirement: &str) -> bool {
+    let requirement = requirement.strip_prefix('v').unwrap_or(requirement);
+    requirement.starts_with(|c: char| c.is_ascii_digit())
+        && requirement
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '+')
+        && !requirement.contains(['x', 'X'])
+}
+
+/// A `key = value` line of a TOML document with the header of the table it belongs to
+/// Only the subset manifests use is understood; arrays may span lines, inline tables may not
+#[derive(Debug)]
+struct TomlEntry {
+    table: String,
+    /// Increments with every header, so entries of one `[[array]]` element can be grouped
+    table_index: usize,
+    key: String,
+    value: String,
+}
+
+fn toml_entries(contents: &str) -> Vec<TomlEntry> {
+    let mut entries = Vec::new();
+    let mut table = String::new();
+    let mut table_index = 0;
+    let mut lines = contents.lines();
+
+    while let Some(line) = lines.next() {
+        let line = strip_toml_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            table = line.trim_matches(['[', ']']).trim().to_string();
+            table_index += 1;
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let mut value = value.trim().to_string();
+        if value.starts_with('[') {
+            // Collect a multi-line array until its brackets balance
+            while value.matches('[').count() > value.matches(']').count() {
+                let Some(next) = lines.next() else { break };
+                value.push(' ');
+                value.push_str(strip_toml_comment(next).trim());
+            }
+        }
+
+        entries.push(TomlEntry {
+            table: table.clone(),
+            table_index,
+            key: key.trim().trim_matches(['"', '\'']).to_string(),
+            value,
+        });
+    }
+
+    entries
+}
+
+fn strip_toml_comment(line: &str) -> &str {
+    let mut quote = None;
+    for (i, c) in line.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('#', None) => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// The string of a TOML value, or the `version` field of an inline table
+fn toml_version(value: &str) -> String {
+    if value.starts_with('{') {
+        return inline_table_field(value, "version").unwrap_or_default();
+    }
+    quoted_strings(value).into_iter().next().unwrap_or_default()
+}
+
+fn inline_table_field(value: &str, field: &str) -> Option<String> {
+    value
+        .trim_matches(['{', '}'])
+        .split(',')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| key.trim() == field)
+        .and_then(|(_, value)| quoted_strings(value).into_iter().next())
+}
+
+fn quoted_strings(value: &str) -> Vec<String> {
+    QUOTED_STRING
+        .captures_iter(value)
+        .filter_map(|c| c.get(1).or_else(|| c.get(2)))
+        .map(|m| m.as_str().to_string())
+        .collect()
+}
+
+fn is_dependency_table(table: &str) -> bool {
+    table
+        .rsplit('.')
+        .next()
+        .is_some_and(|last| last.ends_with("dependencies"))
+}
+
+fn parse_cargo_toml(contents: &str) -> BTreeMap<String, String> {
+    let mut dependencies = BTreeMap::new();
+
+    for entry in toml_entries(contents) {
+        if is_dependency_table(&entry.table) {
+            // `serde.workspace = true` names the dependency before the dot
+            let name = entry.key.split('.').next().unwrap_or(&entry.key);
+            dependencies
+                .entry(name.to_string())
+                .or_insert_with(|| toml_version(&entry.value));
+        } else if let Some((parent, name)) = entry.table.rsplit_once('.')
+            && is_dependency_table(parent)
+        {
+            // `[dependencies.serde]` tables list the fields one per line
+            let version = dependencies
+                .entry(name.trim_matches('"').to_string())
+                .or_default();
+            if entry.key == "version" {
+                *version = toml_version(&entry.value);
+            }
+        }
+    }
+
+    dependencies
+}
+
+fn parse_pyproject(contents: &str) -> BTreeMap<String, String> {
+    let mut dependencies = BTreeMap::new();
+
+    for entry in toml_entries(contents) {
+        let pep508 = (entry.table == "project" && entry.key == "dependencies")
+            || entry.table == "project.optional-dependencies"
+            || entry.table == "dependency-groups";
+
+        if pep508 {
+            for requirement in quoted_strings(&entry.value) {
+                if let Some((name, spec)) = split_pep508(&requirement) {
+                    dependencies.entry(name).or_insert(spec);
+                }
+            }
+        } else if entry.table.starts_with("tool.poetry")
+            && is_dependency_table(&entry.table)
+            && entry.key != "python"
+        {
+            dependencies
+                .entry(normalize_python_name(&entry.key))
+                .or_insert_with(|| toml_version(&entry.value));
+        }
+    }
+
+    dependencies
+}
+
+/// Split a PEP 508 requirement such as "requests[socks]>=2.31; python_version>'3.8'"
+fn split_pep508(requirement: &str) -> Option<(String, String)> {
+    let captures = PEP508_NAME.captures(requirement)?;
+    let name = normalize_python_name(captures.get(1)?.as_str());
+    let spec = requirement[captures.get(0)?.end()..]
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    Some((name, spec))
+}
+
+fn normalize_python_name(name: &str) -> String {
+    name.to_lowercase().replace(['_', '.'], "-")
+}
+
+/// `[[package]]` entries of Cargo.lock, poetry.lock and uv.lock
+fn parse_lock_packages(contents: &str) -> BTreeMap<String, String> {
+    let mut packages = BTreeMap::new();
+    let mut current: Option<(usize, String, String)> = None;
+
+    let mut flush = |package: Option<(usize, String, String)>| {
+        if let Some((_, name, version)) = package
+            && !name.is_empty()
+        {
+            packages.insert(name, version);
+        }
+    };
+
+    for entry in toml_entries(contents) {
+        if entry.table != "package" {
+            continue;
+        }
+        if current
+            .as_ref()
+            .is_none_or(|(index, _, _)| *index != entry.table_index)
+        {
+            flush(current.take());
+            current = Some((entry.table_index, String::new(), String::new()));
+        }
+        if let Some((_, name, version)) = current.as_mut() {
+            match entry.key.as_str() {
+                "name" => *name = toml_version(&entry.value),
+                "version" => *version = toml_version(&entry.value),
+                _ => {}
+            }
+        }
+    }
+    flush(current);
+
+    packages
+}
+
+fn parse_package_json(contents: &str) -> Result<BTreeMap<String, String>> {
+    let manifest: Value = serde_json::from_str(contents).context("Invalid package.json")?;
+
+    let mut dependencies = BTreeMap::new();
+    for section in [
+        "dependencies",
+        "devDependencies",
+        "peerDependencies",
+        "optionalDependencies",
+    ] {
+        if let Some(section) = manifest.get(section).and_then(Value::as_object) {
+            for (name, requirement) in section {
+                dependencies
+                    .entry(name.clone())
+                    .or_insert_with(|| requirement.as_str().unwrap_or_default().to_string());
+            }
+        }
+    }
+
+    Ok(dependencies)
+}
+
+fn parse_package_lock(contents: &str) -> Result<BTreeMap<String, String>> {
+    let lock: Value = serde_json::from_str(contents).context("Invalid package-lock.json")?;
+
+    let mut packages = BTreeMap::new();
+    // Lockfile v2 and v3 key packages by their install path, v1 by name
+    if let Some(entries) = lock.get("packages").and_then(Value::as_object) {
+        for (path, package) in entries {
+            let Some((_, name)) = path.rsplit_once("node_modules/") else {
+                continue;
+            };
+            if let Some(version) = package.get("version").and_then(Value::as_str) {
+                packages.insert(name.to_string(), version.to_string());
+            }
+        }
+    } else if let Some(entries) = lock.get("dependencies").and_then(Value::as_object) {
+        for (name, package) in entries {
+            if let Some(version) = package.get("version").and_then(Value::as_str) {
+                packages.insert(name.clone(), version.to_string());
+            }
+        }
+    }
+
+    Ok(packages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(changes: &[DependencyChange], code: &str) -> Vec<String> {
+        changes
+            .iter()
+            .filter(|c| c.code() == code)
+            .map(|c| c.name().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            ManifestKind::detect("Cargo.toml"),
+            Some(ManifestKind::CargoToml)
+        );
+        assert_eq!(
+            ManifestKind::detect("crates/core/Cargo.toml"),
+            Some(ManifestKind::CargoToml)
+        );
+        assert_eq!(
+            ManifestKind::detect("web/package-lock.json"),
+            Some(ManifestKind::PackageLock)
+        );
+        assert_eq!(
+            ManifestKind::detect("uv.lock"),
+            Some(ManifestKind::PythonLock)
+        );
+        assert_eq!(ManifestKind::detect("src/Cargo.rs"), None);
+        assert!(ManifestKind::CargoLock.is_lockfile());
+        assert!(!ManifestKind::Pyproject.is_lockfile());
+    }
+
+    #[test]
+    fn test_parse_cargo_toml() {
+        let manifest = r#"
+[package]
+name = "demo"
+version = "0.1.0"
+
+[dependencies]
+anyhow = "1.0" # error handling
+serde = { version = "1.0.200", features = ["derive"] }
+local = { path = "../local" }
+shared.workspace = true
+
+[dev-dependencies.tempfile]
+version = "3.10"
+
+[target.'cfg(unix)'.dependencies]
+libc = "0.2"
+"#;
+        let dependencies = ManifestKind::CargoToml.parse(manifest).unwrap();
+        assert_eq!(dependencies.len(), 6);
+        assert_eq!(dependencies["anyhow"], "1.0");
+        assert_eq!(dependencies["serde"], "1.0.200");
+        assert_eq!(dependencies["local"], "");
+        assert_eq!(dependencies["tempfile"], "3.10");
+        assert_eq!(dependencies["libc"], "0.2");
+        assert!(dependencies.contains_key("shared"));
+        assert!(!dependencies.contains_key("name"));
+    }
+
+    #[test]
+    fn test_parse_pyproject() {
+        let manifest = r#"
+[project]
+name = "demo"
+dependencies = [
+    "requests[socks]>=2.31",  # http
+    "Typing_Extensions==4.12.2; python_version < '3.11'",
+]
+
+[project.optional-dependencies]
+dev = ["pytest~=8.0"]
+
+[tool.poetry.group.docs.dependencies]
+python = "^3.10"
+mkdocs = { version = "^1.6" }
+"#;
+        let dependencies = ManifestKind::Pyproject.parse(manifest).unwrap();
+        assert_eq!(dependencies["requests"], ">=2.31");
+        assert_eq!(dependencies["typing-extensions"], "==4.12.2");
+        assert_eq!(dependencies["pytest"], "~=8.0");
+        assert_eq!(dependencies["mkdocs"], "^1.6");
+        assert!(!dependencies.contains_key("python"));
+    }
+
+    #[test]
+    fn test_parse_lockfiles() {
+        let cargo_lock = r#"
+version = 3
+
+[[package]]
+name = "anyhow"
+version = "1.0.86"
+
+[[package]]
+name = "serde"
+version = "1.0.203"
+dependencies = [
+ "serde_derive",
+]
+"#;
+        let packages = ManifestKind::CargoLock.parse(cargo_lock).unwrap();
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages["serde"], "1.0.203");
+
+        let package_lock = r#"{
+            "lockfileVersion": 3,
+            "packages": {
+                "": { "name": "demo" },
+                "node_modules/left-pad": { "version": "1.3.0" },
+                "node_modules/a/node_modules/@scope/b": { "version": "2.0.0" }
+            }
+        }"#;
+        let packages = ManifestKind::PackageLock.parse(package_lock).unwrap();
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages["@scope/b"], "2.0.0");
+
+        assert!(ManifestKind::PackageJson.parse("not json").is_err());
+    }
+
+    #[test]
+    fn test_compare_package_json() {
+        let before = r#"{
+            "dependencies": { "react": "^18.2.0", "lodash": "^4.17.0", "axios": "^1.6.0" },
+            "devDependencies": { "jest": "^29.0.0", "vite": "^5.0.0" }
+        }"#;
+        let after = r#"{
+            "dependencies": { "react": "^18.3.1", "axios": "1.6.8", "zod": "^3.23.0" },
+            "devDependencies": { "jest": "^28.0.0", "vite": "^5.0.0" }
+        }"#;
+
+        let changes =
+            ManifestDiff::compare(ManifestKind::PackageJson, Some(before), Some(after)).unwrap();
+        assert_eq!(names(&changes, ":heavy_plus_sign:"), vec!["zod"]);
+        assert_eq!(names(&changes, ":heavy_minus_sign:"), vec!["lodash"]);
+        assert_eq!(names(&changes, ":arrow_up:"), vec!["react"]);
+        assert_eq!(names(&changes, ":arrow_down:"), vec!["jest"]);
+        assert_eq!(names(&changes, ":pushpin:"), vec!["axios"]);
+        assert_eq!(changes.len(), 5);
+    }
+
+    #[test]
+    fn test_compare_cargo_toml_versions() {
+        let before = "[dependencies]\nregex = \"1.9\"\nserde = \"1.0\"\ntokio = \"1\"\n";
+        let after = "[dependencies]\nregex = \"1.10\"\nserde = { version = \"1.0\", features = [\"derive\"] }\ntokio = \"=1.38.0\"\n";
+
+        let changes =
+            ManifestDiff::compare(ManifestKind::CargoToml, Some(before), Some(after)).unwrap();
+        // 1.10 is newer than 1.9, and adding features is not a version change
+        assert_eq!(
+            changes,
+            vec![
+                DependencyChange::Upgraded {
+                    name: "regex".to_string(),
+                    from: "1.9".to_string(),
+                    to: "1.10".to_string(),
+                },
+                DependencyChange::Pinned {
+                    name: "tokio".to_string(),
+                    version: "=1.38.0".to_string(),
+                },
+            ]
+        );
+
+        // A manifest that is new or deleted adds or removes everything
+        let changes = ManifestDiff::compare(ManifestKind::CargoToml, None, Some(after)).unwrap();
+        assert_eq!(changes.len(), 3);
+        assert!(changes.iter().all(|c| c.code() == ":heavy_plus_sign:"));
+        let changes = ManifestDiff::compare(ManifestKind::CargoToml, Some(before), None).unwrap();
+        assert!(changes.iter().all(|c| c.code() == ":heavy_minus_sign:"));
+    }
+
+    #[test]
+    fn test_signals_prefer_manifests_over_lockfiles() {
+        let changes = vec![
+            (
+                ManifestKind::CargoToml,
+                DependencyChange::Added {
+                    name: "serde".to_string(),
+                    version: "1.0".to_string(),
+                },
+            ),
+            (
+                ManifestKind::CargoLock,
+                DependencyChange::Upgraded {
+                    name: "proc-macro2".to_string(),
+                    from: "1.0.85".to_string(),
+                    to: "1.0.86".to_string(),
+                },
+            ),
+            (
+                ManifestKind::CargoLock,
+                DependencyChange::Added {
+                    name: "serde".to_string(),
+                    version: "1.0.203".to_string(),
+                },
+            ),
+        ];
+
+        let signals = ManifestDiff::signals(&changes);
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].code, ":heavy_plus_sign:");
+        assert_eq!(signals[0].reason, "added dependency serde 1.0");
+        assert!(signals[0].refines.contains(&":package:"));
+
+        // Without manifest changes, the lockfile tells what happened
+        let signals = ManifestDiff::signals(&changes[1..2]);
+        assert_eq!(signals[0].code, ":arrow_up:");
+        assert_eq!(
+            signals[0].reason,
+            "upgraded dependency proc-macro2 1.0.85 → 1.0.86"
+        );
+    }
+
+    #[test]
+    fn test_signals_order_and_summary() {
+        let upgraded = |name: &str| {
+            (
+                ManifestKind::PackageJson,
+                DependencyChange::Upgraded {
+                    name: name.to_string(),
+                    from: "^1.0.0".to_string(),
+                    to: "^2.0.0".to_string(),
+                },
+            )
+        };
+        let mut changes: Vec<_> = ["a", "b", "c", "d"].into_iter().map(upgraded).collect();
+        changes.insert(
+            0,
+            (
+                ManifestKind::PackageJson,
+                DependencyChange::Removed {
+                    name: "e".to_string(),
+                },
+            ),
+        );
+
+        let signals = ManifestDiff::signals(&changes);
+        assert_eq!(signals.len(), 2);
+        assert_eq!(signals[0].code, ":arrow_up:");
+        assert_eq!(
+            signals[0].reason,
+            "upgraded dependencies a ^1.0.0 → ^2.0.0, b ^1.0.0 → ^2.0.0, c ^1.0.0 → ^2.0.0, 1 more"
+        );
+        assert_eq!(signals[1].code, ":heavy_minus_sign:");
+        assert_eq!(signals[1].reason, "removed dependency e");
+    }
+}
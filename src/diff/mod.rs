@@ -0,0 +1,191 @@
+pub mod breaking;
+pub mod content;
+pub mod manifest;
+pub mod paths;
+pub mod summary;
+
+use crate::{
+    commit::GitCommit,
+    config::RepoConfig,
+    status::{FileState, StatusEntry},
+};
+use anyhow::{Context, Result};
+use breaking::BreakingChange;
+use content::ContentAnalyzer;
+use manifest::{DependencyChange, ManifestDiff, ManifestKind};
+use paths::PathRules;
+use summary::DiffSummary;
+
+/// A gitmoji suggested by the staged changes rather than by the commit message
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffSignal {
+    pub code: &'static str,
+    /// Why the signal fired, e.g. "only documentation files are staged"
+    pub reason: String,
+    /// Codes matched from the message that this signal is specific enough to replace
+    pub refines: &'static [&'static str],
+    /// Whether the signal replaces any message match
+    pub decisive: bool,
+}
+
+impl DiffSignal {
+    pub fn new(code: &'static str, reason: impl Into<String>) -> Self {
+        Self {
+            code,
+            reason: reason.into(),
+            refines: &[],
+            decisive: false,
+        }
+    }
+
+    /// Let this signal replace a message match with one of `codes`
+    pub fn refining(mut self, codes: &'static [&'static str]) -> Self {
+        self.refines = codes;
+        self
+    }
+
+    /// Let this signal replace any message match
+    pub fn decisive(mut self) -> Self {
+        self.decisive = true;
+        self
+    }
+
+    /// Whether this signal should replace a message that matched `code` by keyword
+    pub fn overrides(&self, code: &str) -> bool {
+        self.decisive || self.refines.contains(&code)
+    }
+}
+
+/// Derives gitmoji signals from what is staged
+pub struct DiffAnalyzer;
+
+impl DiffAnalyzer {
+    /// Collect the signals for the currently staged changes, strongest first
+    pub fn staged_signals(git: &GitCommit) -> Result<Vec<DiffSignal>> {
+        let config = RepoConfig::load(&git.work_tree()?)?;
+        let rules = PathRules::from_config(&config.paths)?;
+
+        let status = git.status()?;
+        let dependency_changes = Self::dependency_changes(git, &status)?;
+
+        let hunks = ContentAnalyzer::parse_hunks(&git.staged_hunks()?);
+
+        // A likely breaking change comes first, and content signals only fire for narrow
+        // changes, so they are the most specific of the rest
+        let mut signals: Vec<DiffSignal> =
+            BreakingChange::hunk_signal(&hunks).into_iter().collect();
+        signals.extend(ContentAnalyzer::signals(&hunks));
+        signals.extend(rules.classify(&status));
+        signals.extend(ManifestDiff::signals(&dependency_changes));
+        Ok(signals)
+    }
+
+    /// The staged diff cut down to `budget` bytes, for writing a message from it
+    pub fn staged_summary(git: &GitCommit, budget: usize) -> Result<DiffSummary> {
+        let config = RepoConfig::load(&git.work_tree()?)?;
+        let rules = PathRules::from_config(&config.paths)?;
+        Ok(DiffSummary::build(&git.staged_diff()?, budget, &rules))
+    }
+
+    /// Signals carried by the commit message itself, such as breaking change markers
+    pub fn message_signals(message: &str) -> Vec<DiffSignal> {
+        BreakingChange::message_signal(message)
+            .into_iter()
+            .collect()
+    }
+
+    /// Compare every staged manifest with its committed version
+    fn dependency_changes(
+        git: &GitCommit,
+        status: &[StatusEntry],
+    ) -> Result<Vec<(ManifestKind, DependencyChange)>> {
+        let mut changes = Vec::new();
+
+        for entry in status.iter().filter(|e| e.is_staged()) {
+            let Some(kind) = ManifestKind::detect(&entry.path) else {
+                continue;
+            };
+
+            let before = match entry.index {
+                FileState::Added => None,
+                _ => git.head_file(entry.original_path.as_deref().unwrap_or(&entry.path))?,
+            };
+            let after = match entry.index {
+                FileState::Deleted => None,
+                _ => git.staged_file(&entry.path)?,
+            };
+
+            let manifest_changes = ManifestDiff::compare(kind, before.as_deref(), after.as_deref())
+                .with_context(|| format!("Failed to compare {}", entry.path))?;
+            changes.extend(manifest_changes.into_iter().map(|change| (kind, change)));
+        }
+
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::FakeBackend;
+
+    const ZERO_SHA: &str = "0000000000000000000000000000000000000000";
+
+    fn staged_modified(path: &str) -> String {
+        format!("1 M. N... 100644 100644 100644 {ZERO_SHA} {ZERO_SHA} {path}\0")
+    }
+
+    #[test]
+    fn test_staged_signals_dependency_change() {
+        let backend = FakeBackend::new()
+            .with_status(staged_modified("Cargo.toml") + &staged_modified("Cargo.lock"))
+            .with_file("HEAD:Cargo.toml", "[dependencies]\nanyhow = \"1.0\"\n")
+            .with_file(
+                ":Cargo.toml",
+                "[dependencies]\nanyhow = \"1.0\"\nserde = \"1.0\"\n",
+            )
+            .with_file("HEAD:Cargo.lock", "")
+            .with_file(
+                ":Cargo.lock",
+                "[[package]]\nname = \"serde\"\nversion = \"1.0.203\"\n",
+            );
+
+        let signals = DiffAnalyzer::staged_signals(&GitCommit::new(&backend)).unwrap();
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].code, ":heavy_plus_sign:");
+        assert_eq!(signals[0].reason, "added dependency serde 1.0");
+    }
+
+    #[test]
+    fn test_staged_signals_breaking_first() {
+        let diff = "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -3 +2,0 @@\n-pub fn legacy() {}\n";
+        let backend = FakeBackend::new()
+            .with_status(staged_modified("src/lib.rs"))
+            .with_diff("--cached -U0 --no-color --no-ext-diff", diff);
+
+        let signals = DiffAnalyzer::staged_signals(&GitCommit::new(&backend)).unwrap();
+        assert_eq!(signals[0].code, ":boom:");
+        assert_eq!(
+            signals[0].reason,
+            "public items removed or changed: pub fn legacy (src/lib.rs)"
+        );
+        assert!(!signals[0].decisive);
+    }
+
+    #[test]
+    fn test_message_signals() {
+        let signals = DiffAnalyzer::message_signals("fix(parser)!: reject tabs");
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].code, ":boom:");
+        assert!(DiffAnalyzer::message_signals("fix(parser): reject tabs").is_empty());
+    }
+
+    #[test]
+    fn test_staged_signals_paths_first() {
+        let backend = FakeBackend::new().with_status(staged_modified("docs/guide.md"));
+
+        let signals = DiffAnalyzer::staged_signals(&GitCommit::new(&backend)).unwrap();
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].code, ":memo:");
+    }
+}
@@ -0,0 +1,360 @@
+use super::DiffSignal;
+use crate::{
+    config::PathConfig,
+    status::{FileState, StatusEntry},
+};
+use anyhow::{Context, Result};
+use regex::Regex;
+
+/// Kinds of files that map to a single gitmoji when they are all that is staged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathCategory {
+    License,
+    Ci,
+    Tests,
+    Assets,
+    Docs,
+}
+
+impl PathCategory {
+    /// All categories, in the order a path is checked against them
+    pub const ALL: [PathCategory; 5] = [
+        PathCategory::License,
+        PathCategory::Ci,
+        PathCategory::Tests,
+        PathCategory::Assets,
+        PathCategory::Docs,
+    ];
+
+    /// The gitmoji for changes that only touch this category
+    pub fn code(self) -> &'static str {
+        match self {
+            PathCategory::License => ":page_facing_up:",
+            PathCategory::Ci => ":construction_worker:",
+            PathCategory::Tests => ":white_check_mark:",
+            PathCategory::Assets => ":bento:",
+            PathCategory::Docs => ":memo:",
+        }
+    }
+
+    /// Plural description used in signal reasons
+    pub fn description(self) -> &'static str {
+        match self {
+            PathCategory::License => "license files",
+            PathCategory::Ci => "CI files",
+            PathCategory::Tests => "test files",
+            PathCategory::Assets => "asset files",
+            PathCategory::Docs => "documentation files",
+        }
+    }
+
+    fn default_patterns(self) -> &'static [&'static str] {
+        match self {
+            PathCategory::License => &["LICENSE", "LICENSE.*", "LICENSE-*", "COPYING", "COPYING.*"],
+            PathCategory::Ci => &[
+                ".github/workflows/",
+                ".gitlab-ci.yml",
+                ".circleci/",
+                ".buildkite/",
+                ".travis.yml",
+                "azure-pipelines.yml",
+                "Jenkinsfile",
+            ],
+            PathCategory::Tests => &[
+                "tests/",
+                "test/",
+                "**/tests/",
+                "**/__tests__/",
+                "*_test.*",
+                "*.test.*",
+                "*.spec.*",
+                "test_*.py",
+            ],
+            PathCategory::Assets => &[
+                "assets/", "static/", "*.png", "*.jpg", "*.jpeg", "*.gif", "*.svg", "*.ico",
+                "*.webp", "*.woff", "*.woff2", "*.ttf",
+            ],
+            PathCategory::Docs => &["docs/", "doc/", "*.md", "*.rst", "*.adoc"],
+        }
+    }
+
+    fn configured_patterns(self, config: &PathConfig) -> Option<&[String]> {
+        match self {
+            PathCategory::License => config.license.as_deref(),
+            PathCategory::Ci => config.ci.as_deref(),
+            PathCategory::Tests => config.tests.as_deref(),
+            PathCategory::Assets => config.assets.as_deref(),
+            PathCategory::Docs => config.docs.as_deref(),
+        }
+    }
+}
+
+/// Compiled path patterns for every category
+#[derive(Debug, Clone)]
+pub struct PathRules {
+    rules: Vec<(PathCategory, Vec<Regex>)>,
+}
+
+impl Default for PathRules {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PathRules {
+    /// Rules with the built-in patterns
+    pub fn new() -> Self {
+        Self::from_config(&PathConfig::default()).expect("built-in path patterns are valid")
+    }
+
+    /// Rules with the patterns of a repository config, falling back to the built-in ones
+    pub fn from_config(config: &PathConfig) -> Result<Self> {
+        let rules = PathCategory::ALL
+            .into_iter()
+            .map(|category| {
+                let patterns: Vec<&str> = match category.configured_patterns(config) {
+                    Some(patterns) => patterns.iter().map(String::as_str).collect(),
+                    None => category.default_patterns().to_vec(),
+                };
+                let patterns = patterns
+                    .into_iter()
+                    .map(glob_to_regex)
+                    .collect::<Result<_>>()?;
+                Ok((category, patterns))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { rules })
+    }
+
+    /// The first category whose patterns match `path`
+    pub fn categorize(&self, path: &str) -> Option<PathCategory> {
+        self.rules
+            .iter()
+            .find(|(_, patterns)| patterns.iter().any(|pattern| pattern.is_match(path)))
+            .map(|(category, _)| *category)
+    }
+
+    /// Suggest a gitmoji when every staged change is of the same kind
+    pub fn classify(&self, entries: &[StatusEntry]) -> Option<DiffSignal> {
+        let staged: Vec<&StatusEntry> = entries.iter().filter(|e| e.is_staged()).collect();
+        let first = staged.first()?;
+
+        if staged.iter().all(|e| e.index == FileState::Deleted) {
+            return Some(DiffSignal::new(":fire:", "only deletions are staged"));
+        }
+        if staged.iter().all(|e| e.index == FileState::Renamed) {
+            return Some(DiffSignal::new(":truck:", "only renames are staged"));
+        }
+
+        let category = self.categorize(&first.path)?;
+        staged
+            .iter()
+            .all(|e| self.categorize(&e.path) == Some(category))
+            .then(|| {
+                DiffSignal::new(
+                    category.code(),
+                    format!("only {} are staged", category.description()),
+                )
+            })
+    }
+}
+
+/// Compile a gitignore-style glob into a regex over repository-relative paths
+/// Patterns without a slash match the file name at any depth, and a trailing slash matches
+/// everything below a directory
+fn glob_to_regex(pattern: &str) -> Result<Regex> {
+    let mut glob = pattern.trim_start_matches('/').to_string();
+    let anchored = glob.trim_end_matches('/').contains('/') || pattern.starts_with('/');
+    if glob.ends_with('/') {
+        glob.push_str("**");
+    }
+
+    let mut regex = String::from(if anchored { "^" } else { "(^|/)" });
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                // "**/" matches zero or more directories
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    regex.push('$');
+
+    Regex::new(&regex).with_context(|| format!("Invalid path pattern '{pattern}'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status::EntryKind;
+
+    fn staged(index: FileState, path: &str) -> StatusEntry {
+        StatusEntry {
+            kind: if index == FileState::Renamed {
+                EntryKind::RenamedOrCopied
+            } else {
+                EntryKind::Changed
+            },
+            index,
+            worktree: FileState::Unmodified,
+            path: path.to_string(),
+            original_path: None,
+            score: None,
+            submodule: None,
+            conflict: None,
+        }
+    }
+
+    fn modified(paths: &[&str]) -> Vec<StatusEntry> {
+        paths
+            .iter()
+            .map(|path| staged(FileState::Modified, path))
+            .collect()
+    }
+
+    #[test]
+    fn test_glob_to_regex() {
+        let matches = |pattern: &str, path: &str| glob_to_regex(pattern).unwrap().is_match(path);
+
+        assert!(matches("*.md", "README.md"));
+        assert!(matches("*.md", "docs/guide/intro.md"));
+        assert!(!matches("*.md", "README.mdx"));
+        assert!(matches("docs/", "docs/guide/intro.txt"));
+        assert!(!matches("docs/", "src/docs.rs"));
+        assert!(matches(".github/workflows/", ".github/workflows/ci.yml"));
+        assert!(matches("**/tests/", "crates/core/tests/it.rs"));
+        assert!(matches("src/*.rs", "src/lib.rs"));
+        assert!(!matches("src/*.rs", "src/matcher/simple.rs"));
+        assert!(matches("src/**/*.rs", "src/matcher/simple.rs"));
+        assert!(matches("LICENSE-?IT", "LICENSE-MIT"));
+    }
+
+    #[test]
+    fn test_categorize_defaults() {
+        let rules = PathRules::new();
+
+        assert_eq!(rules.categorize("LICENSE"), Some(PathCategory::License));
+        // License files win over the docs extension
+        assert_eq!(rules.categorize("LICENSE.md"), Some(PathCategory::License));
+        assert_eq!(
+            rules.categorize(".github/workflows/ci.yml"),
+            Some(PathCategory::Ci)
+        );
+        assert_eq!(
+            rules.categorize("tests/integration_tests.rs"),
+            Some(PathCategory::Tests)
+        );
+        assert_eq!(
+            rules.categorize("web/app.test.ts"),
+            Some(PathCategory::Tests)
+        );
+        assert_eq!(
+            rules.categorize("assets/logo.svg"),
+            Some(PathCategory::Assets)
+        );
+        assert_eq!(rules.categorize("README.md"), Some(PathCategory::Docs));
+        assert_eq!(rules.categorize("src/main.rs"), None);
+    }
+
+    #[test]
+    fn test_classify_single_category() {
+        let rules = PathRules::new();
+
+        let signal = rules
+            .classify(&modified(&["README.md", "docs/usage.md"]))
+            .unwrap();
+        assert_eq!(signal.code, ":memo:");
+        assert_eq!(signal.reason, "only documentation files are staged");
+
+        let signal = rules.classify(&modified(&["tests/cli.rs"])).unwrap();
+        assert_eq!(signal.code, ":white_check_mark:");
+
+        let signal = rules.classify(&modified(&[".gitlab-ci.yml"])).unwrap();
+        assert_eq!(signal.code, ":construction_worker:");
+
+        let signal = rules.classify(&modified(&["static/favicon.ico"])).unwrap();
+        assert_eq!(signal.code, ":bento:");
+
+        let signal = rules.classify(&modified(&["LICENSE"])).unwrap();
+        assert_eq!(signal.code, ":page_facing_up:");
+    }
+
+    #[test]
+    fn test_classify_mixed_or_unstaged() {
+        let rules = PathRules::new();
+
+        assert_eq!(
+            rules.classify(&modified(&["README.md", "src/lib.rs"])),
+            None
+        );
+        assert_eq!(rules.classify(&[]), None);
+
+        // Unstaged work tree changes are not part of the commit
+        let mut entries = modified(&["README.md"]);
+        entries.push(StatusEntry {
+            index: FileState::Unmodified,
+            worktree: FileState::Modified,
+            ..staged(FileState::Unmodified, "src/lib.rs")
+        });
+        assert_eq!(rules.classify(&entries).unwrap().code, ":memo:");
+    }
+
+    #[test]
+    fn test_classify_deletions_and_renames() {
+        let rules = PathRules::new();
+
+        let entries = vec![
+            staged(FileState::Deleted, "src/legacy.rs"),
+            staged(FileState::Deleted, "README.md"),
+        ];
+        let signal = rules.classify(&entries).unwrap();
+        assert_eq!(signal.code, ":fire:");
+        assert_eq!(signal.reason, "only deletions are staged");
+
+        let entries = vec![staged(FileState::Renamed, "src/new_name.rs")];
+        assert_eq!(rules.classify(&entries).unwrap().code, ":truck:");
+
+        let entries = vec![
+            staged(FileState::Renamed, "src/new_name.rs"),
+            staged(FileState::Deleted, "src/old.rs"),
+        ];
+        assert_eq!(rules.classify(&entries), None);
+    }
+
+    #[test]
+    fn test_configured_patterns_replace_defaults() {
+        let config = PathConfig {
+            docs: Some(vec!["handbook/".to_string()]),
+            ..PathConfig::default()
+        };
+        let rules = PathRules::from_config(&config).unwrap();
+
+        assert_eq!(
+            rules.categorize("handbook/onboarding.txt"),
+            Some(PathCategory::Docs)
+        );
+        assert_eq!(rules.categorize("README.md"), None);
+        // Other categories keep their built-in patterns
+        assert_eq!(rules.categorize("tests/cli.rs"), Some(PathCategory::Tests));
+
+        // Regex metacharacters in patterns are literal
+        let config = PathConfig {
+            tests: Some(vec!["spec/[a]+.rb".to_string()]),
+            ..PathConfig::default()
+        };
+        let rules = PathRules::from_config(&config).unwrap();
+        assert_eq!(rules.categorize("spec/[a]+.rb"), Some(PathCategory::Tests));
+        assert_eq!(rules.categorize("spec/aa.rb"), None);
+    }
+}
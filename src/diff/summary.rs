@@ -0,0 +1,349 @@
+use super::{
+    manifest::ManifestKind,
+    paths::{PathCategory, PathRules},
+};
+
+/// Default size of the diff sent along with a generation request, in bytes
+pub const DEFAULT_BUDGET: usize = 6000;
+
+/// Smallest leftover budget worth filling with the start of a hunk that doesn't fit
+const MIN_PARTIAL_HUNK: usize = 200;
+
+/// One file of a staged diff, split into hunks
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileDiff {
+    pub path: String,
+    pub added: usize,
+    pub removed: usize,
+    /// Hunk texts, each starting with its `@@` line
+    pub hunks: Vec<String>,
+    pub binary: bool,
+}
+
+impl FileDiff {
+    /// How much the file matters to a reader of the commit message, higher first
+    /// Source code comes before tests and docs, generated lockfiles come last
+    fn priority(&self, rules: &PathRules) -> u8 {
+        if ManifestKind::detect(&self.path).is_some_and(ManifestKind::is_lockfile) {
+            return 0;
+        }
+        match rules.categorize(&self.path) {
+            Some(PathCategory::Assets) => 1,
+            Some(PathCategory::Docs | PathCategory::License) => 2,
+            Some(PathCategory::Tests | PathCategory::Ci) => 3,
+            None => 4,
+        }
+    }
+}
+
+/// A staged diff cut down to a size budget
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiffSummary {
+    pub files: Vec<FileDiff>,
+    /// The text sent to the LLM: every file with its line counts and the hunks that fit
+    pub text: String,
+    /// Hunks left out, or cut short, to stay within the budget
+    pub omitted_hunks: usize,
+}
+
+impl DiffSummary {
+    /// Split the output of `git diff` into files and hunks
+    pub fn parse_files(diff: &str) -> Vec<FileDiff> {
+        let mut files: Vec<FileDiff> = Vec::new();
+        let mut in_header = false;
+
+        for line in diff.lines() {
+            if let Some(paths) = line.strip_prefix("diff --git ") {
+                // "a/old b/new", the `+++` line below names the file more reliably
+                let path = paths.rsplit_once(" b/").map_or(paths, |(_, new)| new);
+                files.push(FileDiff {
+                    path: path.to_string(),
+                    ..FileDiff::default()
+                });
+                in_header = true;
+                continue;
+            }
+            let Some(file) = files.last_mut() else {
+                continue;
+            };
+
+            if line.starts_with("@@") {
+                file.hunks.push(format!("{line}\n"));
+                in_header = false;
+            } else if in_header {
+                if let Some(path) = line.strip_prefix("+++ b/") {
+                    file.path = path.to_string();
+                } else if line.starts_with("Binary files ") || line == "GIT binary patch" {
+                    file.binary = true;
+                }
+            } else if let Some(hunk) = file.hunks.last_mut() {
+                if line.starts_with('+') {
+                    file.added += 1;
+                } else if line.starts_with('-') {
+                    file.removed += 1;
+                }
+                hunk.push_str(line);
+                hunk.push('\n');
+            }
+        }
+
+        files
+    }
+
+    /// Summarize a diff within `budget` bytes
+    /// Files are listed and hunks kept by file priority, and left out once the budget is spent
+    pub fn build(diff: &str, budget: usize, rules: &PathRules) -> Self {
+        let files = Self::parse_files(diff);
+
+        let headers: Vec<String> = files
+            .iter()
+            .map(|file| {
+                if file.binary {
+                    format!("{} (binary)\n", file.path)
+                } else {
+                    format!("{} (+{} -{})\n", file.path, file.added, file.removed)
+                }
+            })
+            .collect();
+
+        // Stable sorts, so equally important files keep their diff order
+        let mut file_order: Vec<usize> = (0..files.len()).collect();
+        file_order.sort_by_key(|&f| std::cmp::Reverse(files[f].priority(rules)));
+        let mut order: Vec<(usize, usize)> = file_order
+            .iter()
+            .flat_map(|&f| (0..files[f].hunks.len()).map(move |h| (f, h)))
+            .collect();
+        order.sort_by_key(|&(f, _)| std::cmp::Reverse(files[f].priority(rules)));
+
+        // Unless everything fits, each file needs room for the note on its omitted hunks
+        let whole = headers
+            .iter()
+            .chain(files.iter().flat_map(|file| &file.hunks));
+        let fits = whole.map(String::len).sum::<usize>() <= budget;
+        let listing_cost = |f: usize| {
+            let hunks = files[f].hunks.len();
+            let note = if fits || hunks == 0 {
+                0
+            } else {
+                Self::omitted_note(hunks, hunks).len()
+            };
+            headers[f].len() + note
+        };
+
+        let mut listed = vec![true; files.len()];
+        let mut remaining = budget;
+        let listing: usize = (0..files.len()).map(listing_cost).sum();
+        if listing <= budget {
+            remaining -= listing;
+        } else {
+            // Too many files to list them all, the least important ones are only counted
+            remaining = budget.saturating_sub(Self::unlisted_note(files.len()).len());
+            for &f in &file_order {
+                listed[f] = listing_cost(f) <= remaining;
+                if listed[f] {
+                    remaining -= listing_cost(f);
+                }
+            }
+        }
+
+        let mut kept: Vec<Vec<Option<String>>> = files
+            .iter()
+            .map(|file| vec![None; file.hunks.len()])
+            .collect();
+        let mut omitted_hunks = 0;
+        for (f, h) in order {
+            let hunk = &files[f].hunks[h];
+            if !listed[f] {
+                omitted_hunks += 1;
+            } else if hunk.len() <= remaining {
+                remaining -= hunk.len();
+                kept[f][h] = Some(hunk.clone());
+            } else if remaining >= MIN_PARTIAL_HUNK {
+                let partial = Self::truncate_lines(hunk, remaining);
+                remaining -= partial.len();
+                kept[f][h] = Some(partial);
+                omitted_hunks += 1;
+            } else {
+                omitted_hunks += 1;
+            }
+        }
+
+        let mut text = String::new();
+        for (f, hunks) in kept.into_iter().enumerate() {
+            if !listed[f] {
+                continue;
+            }
+            text.push_str(&headers[f]);
+            let skipped = hunks.iter().filter(|hunk| hunk.is_none()).count();
+            for hunk in hunks.into_iter().flatten() {
+                text.push_str(&hunk);
+            }
+            if skipped > 0 {
+                text.push_str(&Self::omitted_note(skipped, files[f].hunks.len()));
+            }
+        }
+        let unlisted = listed.iter().filter(|listed| !**listed).count();
+        let unlisted_note = Self::unlisted_note(unlisted);
+        if unlisted > 0 && text.len() + unlisted_note.len() <= budget {
+            text.push_str(&unlisted_note);
+        }
+
+        Self {
+            files,
+            text,
+            omitted_hunks,
+        }
+    }
+
+    fn omitted_note(skipped: usize, hunks: usize) -> String {
+        format!("[{skipped} of {hunks} hunks omitted]\n")
+    }
+
+    fn unlisted_note(files: usize) -> String {
+        format!("[{files} more files not listed]\n")
+    }
+
+    /// The leading whole lines of a hunk that fit in `budget`, marked as cut short
+    fn truncate_lines(hunk: &str, budget: usize) -> String {
+        const MARKER: &str = "[hunk truncated]\n";
+
+        let mut partial = String::new();
+        for line in hunk.split_inclusive('\n') {
+            if partial.len() + line.len() + MARKER.len() > budget {
+                break;
+            }
+            partial.push_str(line);
+        }
+        partial.push_str(MARKER);
+        partial
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "\
+diff --git a/Cargo.lock b/Cargo.lock
+index 1111111..2222222 100644
+--- a/Cargo.lock
++++ b/Cargo.lock
+@@ -10,3 +10,3 @@
+ name = \"serde\"
+-version = \"1.0.200\"
++version = \"1.0.203\"
+diff --git a/src/lib.rs b/src/lib.rs
+index 3333333..4444444 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,2 +1,3 @@
+ pub fn parse() {
+--- not a header
++    validate();
+ }
+diff --git a/logo.png b/logo.png
+new file mode 100644
+index 0000000..5555555
+Binary files /dev/null and b/logo.png differ
+";
+
+    #[test]
+    fn test_parse_files() {
+        let files = DiffSummary::parse_files(DIFF);
+        assert_eq!(files.len(), 3);
+
+        assert_eq!(files[0].path, "Cargo.lock");
+        assert_eq!((files[0].added, files[0].removed), (1, 1));
+
+        // A removed line that looks like a header is still content
+        assert_eq!(files[1].path, "src/lib.rs");
+        assert_eq!((files[1].added, files[1].removed), (1, 1));
+        assert_eq!(files[1].hunks.len(), 1);
+        assert!(files[1].hunks[0].starts_with("@@ -1,2 +1,3 @@\n"));
+        assert!(files[1].hunks[0].contains("--- not a header\n"));
+
+        assert!(files[2].binary);
+        assert!(files[2].hunks.is_empty());
+    }
+
+    #[test]
+    fn test_build_within_budget_keeps_everything() {
+        let summary = DiffSummary::build(DIFF, DEFAULT_BUDGET, &PathRules::new());
+
+        assert_eq!(summary.omitted_hunks, 0);
+        assert!(
+            summary
+                .text
+                .starts_with("Cargo.lock (+1 -1)\n@@ -10,3 +10,3 @@\n")
+        );
+        assert!(
+            summary
+                .text
+                .contains("src/lib.rs (+1 -1)\n@@ -1,2 +1,3 @@\n")
+        );
+        assert!(summary.text.ends_with("logo.png (binary)\n"));
+    }
+
+    #[test]
+    fn test_build_drops_low_priority_hunks_first() {
+        let headers = "Cargo.lock (+1 -1)\nsrc/lib.rs (+1 -1)\nlogo.png (binary)\n".len();
+        let notes = 2 * "[1 of 1 hunks omitted]\n".len();
+        let files = DiffSummary::parse_files(DIFF);
+        let budget = headers + notes + files[1].hunks[0].len();
+
+        let summary = DiffSummary::build(DIFF, budget, &PathRules::new());
+        assert_eq!(summary.omitted_hunks, 1);
+        assert!(summary.text.contains("[1 of 1 hunks omitted]"));
+        assert!(summary.text.contains("validate();"));
+        assert!(!summary.text.contains("1.0.203"));
+        assert!(summary.text.len() <= budget);
+    }
+
+    #[test]
+    fn test_build_counts_headers_and_notes_in_budget() {
+        let files: String = (0..50)
+            .map(|i| {
+                format!(
+                    "diff --git a/src/file{i}.rs b/src/file{i}.rs\n--- a/src/file{i}.rs\n+++ b/src/file{i}.rs\n@@ -1 +1 @@\n-old {i}\n+new {i}\n"
+                )
+            })
+            .collect();
+        let diff = format!("{DIFF}{files}");
+
+        for budget in [0, 40, 100, 300, 1000, 2000] {
+            let summary = DiffSummary::build(&diff, budget, &PathRules::new());
+            assert!(
+                summary.text.len() <= budget,
+                "{} bytes over a budget of {budget}:\n{}",
+                summary.text.len(),
+                summary.text
+            );
+            assert_eq!(summary.files.len(), 53);
+        }
+
+        // Files that can't be listed are counted, the lockfile first
+        let summary = DiffSummary::build(&diff, 300, &PathRules::new());
+        assert!(summary.text.starts_with("src/lib.rs (+1 -1)\n"));
+        assert!(!summary.text.contains("Cargo.lock"));
+        assert!(summary.text.ends_with("more files not listed]\n"));
+        assert_eq!(summary.omitted_hunks, 52);
+    }
+
+    #[test]
+    fn test_build_truncates_large_hunk() {
+        let body: String = (0..100).map(|i| format!("+line {i}\n")).collect();
+        let diff = format!(
+            "diff --git a/src/big.rs b/src/big.rs\n--- a/src/big.rs\n+++ b/src/big.rs\n@@ -0,0 +1,100 @@\n{body}"
+        );
+
+        let summary = DiffSummary::build(&diff, 400, &PathRules::new());
+        assert_eq!(summary.omitted_hunks, 1);
+        assert!(
+            summary
+                .text
+                .starts_with("src/big.rs (+100 -0)\n@@ -0,0 +1,100 @@\n+line 0\n")
+        );
+        assert!(summary.text.ends_with("[hunk truncated]\n"));
+        assert!(summary.text.len() <= 400);
+    }
+}
@@ -13,6 +13,27 @@ struct Gitmoji {
     code: String,
     description: String,
     name: String,
+    semver: Option<SemverImpact>,
+}
+
+/// The version bump a gitmoji calls for, ordered from smallest to largest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SemverImpact {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl SemverImpact {
+    /// Lowercase name, as in gitmojis.json
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SemverImpact::Patch => "patch",
+            SemverImpact::Minor => "minor",
+            SemverImpact::Major => "major",
+        }
+    }
 }
 
 /// Emoji lookup functionality
@@ -24,11 +45,42 @@ impl EmojiLookup {
         EMOJI_MAP.get(code).copied()
     }
 
+    /// The version bump a gitmoji calls for, None when it doesn't affect the version
+    pub fn semver(code: &str) -> Option<SemverImpact> {
+        SEMVER_MAP.get(code).copied()
+    }
+
     /// Get all available emoji codes
     pub fn all_codes() -> Vec<&'static str> {
         EMOJI_MAP.keys().copied().collect()
     }
 
+    /// Split a leading gitmoji off a commit subject
+    /// Accepts both the shortcode form (":bug: fix") and the Unicode form ("🐛 fix"),
+    /// and returns the canonical emoji code together with the remaining text
+    pub fn split_leading(text: &str) -> Option<(&'static str, &str)> {
+        let trimmed = text.trim_start();
+
+        if let Some(after_colon) = trimmed.strip_prefix(':') {
+            let end = after_colon.find(':')?;
+            let candidate = &trimmed[..end + 2];
+            let (code, _) = EMOJI_MAP.get_key_value(candidate)?;
+            return Some((code, trimmed[candidate.len()..].trim_start()));
+        }
+
+        // Variation selectors (U+FE0F) are optional when typing emoji, so compare without them
+        // and prefer the longest glyph to keep ZWJ sequences intact
+        EMOJI_MAP
+            .iter()
+            .map(|(code, emoji)| (*code, emoji.trim_end_matches('\u{fe0f}')))
+            .filter(|(_, bare)| !bare.is_empty() && trimmed.starts_with(bare))
+            .max_by_key(|(_, bare)| bare.len())
+            .map(|(code, bare)| {
+                let rest = trimmed[bare.len()..].trim_start_matches('\u{fe0f}');
+                (code, rest.trim_start())
+            })
+    }
+
     /// Load emoji mapping from gitmojis.json file
     fn load_from_json() -> Result<HashMap<&'static str, &'static str>, Box<dyn std::error::Error>> {
         let json_content = include_str!("../fixtures/gitmojis.json");
@@ -46,6 +98,23 @@ impl EmojiLookup {
         Ok(map)
     }
 
+    /// Load the semver impact of every gitmoji that has one from gitmojis.json
+    fn load_semver_from_json()
+    -> Result<HashMap<&'static str, SemverImpact>, Box<dyn std::error::Error>> {
+        let json_content = include_str!("../fixtures/gitmojis.json");
+        let gitmoji_data: GitmojiData = serde_json::from_str(json_content)?;
+
+        let mut map = HashMap::new();
+        for gitmoji in gitmoji_data.gitmojis {
+            if let Some(semver) = gitmoji.semver {
+                let code: &'static str = Box::leak(gitmoji.code.into_boxed_str());
+                map.insert(code, semver);
+            }
+        }
+
+        Ok(map)
+    }
+
     /// Fallback emoji mapping if JSON loading fails
     fn default_emoji_map() -> HashMap<&'static str, &'static str> {
         let mut map = HashMap::new();
@@ -143,10 +212,28 @@ pub static EMOJI_MAP: std::sync::LazyLock<HashMap<&'static str, &'static str>> =
         }
     });
 
+/// Semver impact of each gitmoji code that has one, loaded from gitmojis.json
+/// Empty if the data can't be loaded, so every commit then counts as not affecting the version
+pub static SEMVER_MAP: std::sync::LazyLock<HashMap<&'static str, SemverImpact>> =
+    std::sync::LazyLock::new(|| EmojiLookup::load_semver_from_json().unwrap_or_default());
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_semver_impact() {
+        assert_eq!(EmojiLookup::semver(":boom:"), Some(SemverImpact::Major));
+        assert_eq!(EmojiLookup::semver(":sparkles:"), Some(SemverImpact::Minor));
+        assert_eq!(EmojiLookup::semver(":bug:"), Some(SemverImpact::Patch));
+        assert_eq!(EmojiLookup::semver(":memo:"), None);
+        assert_eq!(EmojiLookup::semver(":nonexistent:"), None);
+
+        assert!(SemverImpact::Major > SemverImpact::Minor);
+        assert!(SemverImpact::Minor > SemverImpact::Patch);
+        assert!(SEMVER_MAP.keys().all(|code| EMOJI_MAP.contains_key(code)));
+    }
+
     #[test]
     fn test_code_to_unicode_valid_codes() {
         // Test some common emoji codes
@@ -182,6 +269,34 @@ mod tests {
         assert!(codes.contains(&":fire:"));
     }
 
+    #[test]
+    fn test_split_leading() {
+        // Shortcode form
+        assert_eq!(
+            EmojiLookup::split_leading(":bug: fix login"),
+            Some((":bug:", "fix login"))
+        );
+
+        // Unicode form, with and without the variation selector
+        assert_eq!(
+            EmojiLookup::split_leading("🐛 fix login"),
+            Some((":bug:", "fix login"))
+        );
+        assert_eq!(
+            EmojiLookup::split_leading("⚡️ speed up parser"),
+            Some((":zap:", "speed up parser"))
+        );
+        assert_eq!(
+            EmojiLookup::split_leading("⚡ speed up parser"),
+            Some((":zap:", "speed up parser"))
+        );
+
+        // Unknown codes and plain text are not gitmojis
+        assert_eq!(EmojiLookup::split_leading(":nonexistent: text"), None);
+        assert_eq!(EmojiLookup::split_leading("fix: login"), None);
+        assert_eq!(EmojiLookup::split_leading(""), None);
+    }
+
     #[test]
     fn test_emoji_map_initialization() {
         // Test that the static map is properly initialized
@@ -0,0 +1,822 @@
+use crate::{
+    commit::GitCommit,
+    emoji::EmojiLookup,
+    lint::{CommitLinter, RangeLintOptions, RangeLintReport},
+    matcher::GitmojiMatcher,
+    special::{RepoState, SpecialCommit},
+};
+use anyhow::{Context, Result, anyhow, bail};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Marker git places above the diff in `git commit -v`; everything below it is ignored
+const SCISSORS: &str = "------------------------ >8 ------------------------";
+
+/// Marker line identifying hook scripts written by amoji
+const INSTALL_MARKER: &str = "# Installed by amoji (auto-gitmoji)";
+
+/// Suffix given to a pre-existing hook that amoji chains to
+const CHAINED_SUFFIX: &str = ".amoji-chained";
+
+/// Git hooks amoji can install
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HookKind {
+    PrepareCommitMsg,
+    CommitMsg,
+}
+
+impl HookKind {
+    /// All hooks amoji knows how to manage
+    pub const ALL: [HookKind; 2] = [HookKind::PrepareCommitMsg, HookKind::CommitMsg];
+
+    /// The hook file name git looks for
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            HookKind::PrepareCommitMsg => "prepare-commit-msg",
+            HookKind::CommitMsg => "commit-msg",
+        }
+    }
+
+    /// Parse a hook file name
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.file_name() == name)
+    }
+
+    /// Shell script that runs the chained hook first, then amoji
+    fn script(&self) -> String {
+        let name = self.file_name();
+        let missing_amoji = self.missing_amoji();
+        format!(
+            r#"#!/bin/sh
+{INSTALL_MARKER}. Remove with `amoji hook uninstall`.
+chained="$(dirname "$0")/{name}{CHAINED_SUFFIX}"
+if [ -x "$chained" ]; then
+    "$chained" "$@" || exit $?
+fi
+{missing_amoji}
+exec amoji hook {name} "$@"
+"#
+        )
+    }
+
+    /// Script lines for when amoji is not on PATH, as in many GUI clients and CI jobs
+    /// Leaving the message alone is harmless, but a skipped check must not go unnoticed
+    fn missing_amoji(&self) -> &'static str {
+        match self {
+            HookKind::PrepareCommitMsg => "command -v amoji >/dev/null 2>&1 || exit 0",
+            HookKind::CommitMsg => {
+                r#"if ! command -v amoji >/dev/null 2>&1; then
+    echo "amoji: not found on PATH, the commit message was not checked for a gitmoji" >&2
+    exit 0
+fi"#
+            }
+        }
+    }
+}
+
+/// Installation state of a single hook
+#[derive(Debug, Clone, PartialEq)]
+pub enum HookStatus {
+    /// No hook file exists
+    Missing,
+    /// amoji's script is installed, optionally chaining to a previous hook
+    Installed { chained: bool },
+    /// A hook not written by amoji is installed
+    Foreign,
+}
+
+/// Install, remove and inspect amoji's hook scripts in a hooks directory
+pub struct HookInstaller {
+    hooks_dir: PathBuf,
+}
+
+impl HookInstaller {
+    /// Create an installer for the given hooks directory
+    pub fn new(hooks_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            hooks_dir: hooks_dir.into(),
+        }
+    }
+
+    /// The directory hooks are installed into
+    pub fn hooks_dir(&self) -> &Path {
+        &self.hooks_dir
+    }
+
+    /// Install the hook, moving any existing foreign hook aside so it keeps running first
+    pub fn install(&self, kind: HookKind) -> Result<HookStatus> {
+        fs::create_dir_all(&self.hooks_dir).with_context(|| {
+            format!(
+                "Failed to create hooks directory {}",
+                self.hooks_dir.display()
+            )
+        })?;
+
+        let path = self.hook_path(kind);
+        let chained_path = self.chained_path(kind);
+
+        if self.status(kind)? == HookStatus::Foreign {
+            if chained_path.exists() {
+                bail!(
+                    "Cannot chain {}: {} already exists",
+                    path.display(),
+                    chained_path.display()
+                );
+            }
+            fs::rename(&path, &chained_path)
+                .with_context(|| format!("Failed to move existing hook {}", path.display()))?;
+        }
+
+        fs::write(&path, kind.script())
+            .with_context(|| format!("Failed to write hook {}", path.display()))?;
+        Self::make_executable(&path)?;
+
+        self.status(kind)
+    }
+
+    /// Remove amoji's hook and restore the hook it chained to
+    /// Hooks not written by amoji are never touched
+    pub fn uninstall(&self, kind: HookKind) -> Result<HookStatus> {
+        if !matches!(self.status(kind)?, HookStatus::Installed { .. }) {
+            return self.status(kind);
+        }
+
+        let path = self.hook_path(kind);
+        let chained_path = self.chained_path(kind);
+
+        fs::remove_file(&path)
+            .with_context(|| format!("Failed to remove hook {}", path.display()))?;
+        if chained_path.exists() {
+            fs::rename(&chained_path, &path)
+                .with_context(|| format!("Failed to restore hook {}", path.display()))?;
+        }
+
+        self.status(kind)
+    }
+
+    /// Report whether the hook is installed
+    pub fn status(&self, kind: HookKind) -> Result<HookStatus> {
+        let path = self.hook_path(kind);
+        if !path.exists() {
+            return Ok(HookStatus::Missing);
+        }
+
+        let contents =
+            fs::read(&path).with_context(|| format!("Failed to read hook {}", path.display()))?;
+        if String::from_utf8_lossy(&contents).contains(INSTALL_MARKER) {
+            Ok(HookStatus::Installed {
+                chained: self.chained_path(kind).exists(),
+            })
+        } else {
+            Ok(HookStatus::Foreign)
+        }
+    }
+
+    fn hook_path(&self, kind: HookKind) -> PathBuf {
+        self.hooks_dir.join(kind.file_name())
+    }
+
+    fn chained_path(&self, kind: HookKind) -> PathBuf {
+        self.hooks_dir
+            .join(format!("{}{CHAINED_SUFFIX}", kind.file_name()))
+    }
+
+    #[cfg(unix)]
+    fn make_executable(path: &Path) -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+            .with_context(|| format!("Failed to make hook executable {}", path.display()))
+    }
+
+    #[cfg(not(unix))]
+    fn make_executable(_path: &Path) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Source of the commit message, as passed to the prepare-commit-msg hook
+#[derive(Debug, Clone, PartialEq)]
+pub enum MessageSource {
+    /// Plain `git commit`, the editor will be opened
+    Editor,
+    /// `-m` or `-F`
+    Message,
+    /// `-t` or `commit.template`
+    Template,
+    /// A merge commit or `.git/MERGE_MSG`
+    Merge,
+    /// `.git/SQUASH_MSG`
+    Squash,
+    /// `-c`, `-C` or `--amend`
+    Commit,
+}
+
+impl MessageSource {
+    /// Parse the second hook argument; a missing argument means the editor source
+    pub fn parse(source: Option<&str>) -> Self {
+        match source {
+            Some("message") => MessageSource::Message,
+            Some("template") => MessageSource::Template,
+            Some("merge") => MessageSource::Merge,
+            Some("squash") => MessageSource::Squash,
+            Some("commit") => MessageSource::Commit,
+            _ => MessageSource::Editor,
+        }
+    }
+
+    /// Whether messages from this source are written by git and must be kept verbatim
+    /// unless a merge or cherry-pick in progress says otherwise
+    fn is_generated(&self) -> bool {
+        matches!(self, MessageSource::Merge | MessageSource::Squash)
+    }
+}
+
+/// A ref update as passed to the pre-receive hook on stdin
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefUpdate {
+    pub old_sha: String,
+    pub new_sha: String,
+    pub ref_name: String,
+}
+
+impl RefUpdate {
+    /// Parse the `<old> <new> <ref>` lines git writes to the pre-receive hook
+    pub fn parse_lines(input: &str) -> Result<Vec<Self>> {
+        input
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let mut fields = line.split_whitespace();
+                match (fields.next(), fields.next(), fields.next()) {
+                    (Some(old_sha), Some(new_sha), Some(ref_name)) => Ok(Self {
+                        old_sha: old_sha.to_string(),
+                        new_sha: new_sha.to_string(),
+                        ref_name: ref_name.to_string(),
+                    }),
+                    _ => Err(anyhow!("Malformed pre-receive input line: '{line}'")),
+                }
+            })
+            .collect()
+    }
+
+    /// Whether the ref is being deleted
+    pub fn is_delete(&self) -> bool {
+        Self::is_null_sha(&self.new_sha)
+    }
+
+    /// Whether the ref is being created
+    pub fn is_create(&self) -> bool {
+        Self::is_null_sha(&self.old_sha)
+    }
+
+    /// Revision arguments selecting the commits this update introduces
+    /// Returns None for deletions, which introduce no commits
+    pub fn revisions(&self) -> Option<Vec<String>> {
+        if self.is_delete() {
+            None
+        } else if self.is_create() {
+            // Anything not already reachable from an existing ref is new
+            Some(vec![
+                self.new_sha.clone(),
+                "--not".to_string(),
+                "--all".to_string(),
+            ])
+        } else {
+            Some(vec![format!("{}..{}", self.old_sha, self.new_sha)])
+        }
+    }
+
+    fn is_null_sha(sha: &str) -> bool {
+        !sha.is_empty() && sha.chars().all(|c| c == '0')
+    }
+}
+
+/// Git hook entry points
+pub struct GitHook;
+
+impl GitHook {
+    /// Run the prepare-commit-msg hook: emojify the subject of the message file in place
+    /// Returns the new subject line when the file was rewritten
+    pub fn prepare_commit_msg(
+        matcher: &dyn GitmojiMatcher,
+        path: &Path,
+        source: &MessageSource,
+        state: &RepoState,
+        comment_char: char,
+    ) -> Result<Option<String>> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read commit message file {}", path.display()))?;
+
+        let Some(rewritten) =
+            Self::emojify_message(matcher, &contents, source, state, comment_char)?
+        else {
+            return Ok(None);
+        };
+
+        fs::write(path, &rewritten)
+            .with_context(|| format!("Failed to write commit message file {}", path.display()))?;
+
+        Ok(Self::subject_line(&rewritten, comment_char).map(|(_, subject)| subject.to_string()))
+    }
+
+    /// Emojify the subject line of a commit message
+    /// Comment lines, the verbose diff section and every other line are kept as-is.
+    /// Merges, reverts and cherry-picks get their own gitmoji instead of a match, and
+    /// fixup!/squash!/amend! subjects are never prefixed.
+    /// Returns None when the message should be left alone.
+    pub fn emojify_message(
+        matcher: &dyn GitmojiMatcher,
+        contents: &str,
+        source: &MessageSource,
+        state: &RepoState,
+        comment_char: char,
+    ) -> Result<Option<String>> {
+        if *source == MessageSource::Squash {
+            return Ok(None);
+        }
+
+        // Nothing typed yet (e.g. the editor is about to open), nothing to do
+        let Some((index, subject)) = Self::subject_line(contents, comment_char) else {
+            return Ok(None);
+        };
+
+        // Already carries a gitmoji, don't add a second one
+        if EmojiLookup::split_leading(subject).is_some() {
+            return Ok(None);
+        }
+
+        let formatted_message = match SpecialCommit::detect(subject, state) {
+            Some(special) => match special.code() {
+                Some(code) => GitCommit::format_message(code, subject),
+                None => return Ok(None),
+            },
+            None if source.is_generated() => return Ok(None),
+            None => match matcher.match_emoji(subject)? {
+                Some((_emoji_code, formatted_message)) => formatted_message,
+                None => return Ok(None),
+            },
+        };
+
+        let rewritten: String = contents
+            .split_inclusive('\n')
+            .enumerate()
+            .map(|(i, line)| {
+                if i == index {
+                    let ending = &line[line.trim_end_matches(['\r', '\n']).len()..];
+                    format!("{formatted_message}{ending}")
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect();
+
+        Ok(Some(rewritten))
+    }
+
+    /// Run the pre-receive policy: lint the new commits of every pushed ref
+    pub fn pre_receive(
+        git: &GitCommit,
+        input: &str,
+        options: &RangeLintOptions,
+    ) -> Result<Vec<(RefUpdate, RangeLintReport)>> {
+        RefUpdate::parse_lines(input)?
+            .into_iter()
+            .map(|update| {
+                let commits = match update.revisions() {
+                    Some(revisions) => {
+                        let revisions: Vec<&str> = revisions.iter().map(String::as_str).collect();
+                        git.log_revisions(&revisions)?
+                    }
+                    None => Vec::new(),
+                };
+                let report = CommitLinter::lint_commits(&commits, options)?;
+                Ok((update, report))
+            })
+            .collect()
+    }
+
+    /// The message as git will record it: every non-comment line above the scissors marker
+    pub fn message_text(contents: &str, comment_char: char) -> String {
+        let scissors = format!("{comment_char} {SCISSORS}");
+
+        contents
+            .lines()
+            .take_while(|line| *line != scissors)
+            .filter(|line| !line.starts_with(comment_char))
+            .map(|line| format!("{line}\n"))
+            .collect::<String>()
+            .trim_start()
+            .to_string()
+    }
+
+    /// Find the subject line: the first non-blank, non-comment line above the scissors marker
+    /// Returns the line index and the trimmed subject
+    pub(crate) fn subject_line(contents: &str, comment_char: char) -> Option<(usize, &str)> {
+        let scissors = format!("{comment_char} {SCISSORS}");
+
+        for (index, line) in contents.lines().enumerate() {
+            if line == scissors {
+                break;
+            }
+            if line.starts_with(comment_char) || line.trim().is_empty() {
+                continue;
+            }
+            return Some((index, line.trim()));
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{backend::FakeBackend, commit::CommitInfo, matcher::MatcherFactory};
+
+    #[test]
+    fn test_message_source_parse() {
+        assert_eq!(MessageSource::parse(None), MessageSource::Editor);
+        assert_eq!(
+            MessageSource::parse(Some("message")),
+            MessageSource::Message
+        );
+        assert_eq!(
+            MessageSource::parse(Some("template")),
+            MessageSource::Template
+        );
+        assert_eq!(MessageSource::parse(Some("merge")), MessageSource::Merge);
+        assert_eq!(MessageSource::parse(Some("squash")), MessageSource::Squash);
+        assert_eq!(MessageSource::parse(Some("commit")), MessageSource::Commit);
+    }
+
+    #[test]
+    fn test_emojify_message_subject_only() {
+        let matcher = MatcherFactory::simple();
+
+        let result = GitHook::emojify_message(
+            matcher.as_ref(),
+            "fix login bug\n",
+            &MessageSource::Message,
+            &RepoState::default(),
+            '#',
+        )
+        .unwrap();
+
+        assert_eq!(result, Some(":bug: fix login bug\n".to_string()));
+    }
+
+    #[test]
+    fn test_emojify_message_keeps_body_and_comments() {
+        let matcher = MatcherFactory::simple();
+
+        let contents = "\
+# leading comment
+fix login bug
+
+Longer explanation of the fix.
+
+# Please enter the commit message for your changes.
+# ------------------------ >8 ------------------------
+# Do not modify or remove the line above.
+diff --git a/src/lib.rs b/src/lib.rs
++fix everything
+";
+
+        let result = GitHook::emojify_message(
+            matcher.as_ref(),
+            contents,
+            &MessageSource::Message,
+            &RepoState::default(),
+            '#',
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(
+            result,
+            contents.replacen("fix login bug", ":bug: fix login bug", 1)
+        );
+        assert!(result.ends_with("+fix everything\n"));
+    }
+
+    #[test]
+    fn test_emojify_message_ignores_verbose_diff() {
+        let matcher = MatcherFactory::simple();
+
+        // Only the diff below the scissors has text, so there is no subject yet
+        let contents = "\n# ------------------------ >8 ------------------------\nfix stuff\n";
+
+        let result = GitHook::emojify_message(
+            matcher.as_ref(),
+            contents,
+            &MessageSource::Editor,
+            &RepoState::default(),
+            '#',
+        )
+        .unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_message_text_strips_comments_and_diff() {
+        let contents = "\n# Please enter the commit message\nfeat!: drop v1\n\nBREAKING CHANGE: gone\n# ------------------------ >8 ------------------------\n+BREAKING CHANGE: in the diff\n";
+        assert_eq!(
+            GitHook::message_text(contents, '#'),
+            "feat!: drop v1\n\nBREAKING CHANGE: gone\n"
+        );
+    }
+
+    #[test]
+    fn test_emojify_message_leaves_generated_sources() {
+        let matcher = MatcherFactory::simple();
+
+        for source in [MessageSource::Merge, MessageSource::Squash] {
+            let result = GitHook::emojify_message(
+                matcher.as_ref(),
+                "Merge branch 'feature'\n",
+                &source,
+                &RepoState::default(),
+                '#',
+            )
+            .unwrap();
+            assert!(result.is_none(), "Should leave {source:?} alone");
+        }
+    }
+
+    #[test]
+    fn test_emojify_message_special_commits() {
+        let matcher = MatcherFactory::simple();
+        let emojify = |contents: &str, source: MessageSource, state: &RepoState| {
+            GitHook::emojify_message(matcher.as_ref(), contents, &source, state, '#').unwrap()
+        };
+        let clean = RepoState::default();
+
+        // Prefixing would stop `rebase --autosquash` from finding the target commit
+        for contents in [
+            "fixup! add stats\n",
+            "squash! fix login\n",
+            "amend! add docs\n",
+        ] {
+            assert_eq!(emojify(contents, MessageSource::Message, &clean), None);
+        }
+
+        assert_eq!(
+            emojify("Revert \"add stats\"\n", MessageSource::Message, &clean),
+            Some(":rewind: Revert \"add stats\"\n".to_string())
+        );
+
+        let merging = RepoState {
+            merging: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            emojify("Merge branch 'feature'\n", MessageSource::Merge, &merging),
+            Some(":twisted_rightwards_arrows: Merge branch 'feature'\n".to_string())
+        );
+
+        let picking = RepoState {
+            cherry_pick: Some(":zap: speed up parser".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            emojify("speed up parser\n", MessageSource::Merge, &picking),
+            Some(":zap: speed up parser\n".to_string())
+        );
+        let picking = RepoState {
+            cherry_pick: Some("speed up parser".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            emojify("speed up parser\n", MessageSource::Message, &picking),
+            None
+        );
+    }
+
+    #[test]
+    fn test_emojify_message_existing_gitmoji() {
+        let matcher = MatcherFactory::simple();
+
+        for contents in [":bug: fix login bug\n", "🐛 fix login bug\n"] {
+            let result = GitHook::emojify_message(
+                matcher.as_ref(),
+                contents,
+                &MessageSource::Commit,
+                &RepoState::default(),
+                '#',
+            )
+            .unwrap();
+            assert!(result.is_none(), "Should not double-prefix: '{contents}'");
+        }
+    }
+
+    #[test]
+    fn test_emojify_message_custom_comment_char() {
+        let matcher = MatcherFactory::simple();
+
+        let contents = "; comment\nadd docs\r\n";
+        let result = GitHook::emojify_message(
+            matcher.as_ref(),
+            contents,
+            &MessageSource::Message,
+            &RepoState::default(),
+            ';',
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(result, "; comment\n:memo: add docs\r\n");
+    }
+
+    const NULL_SHA: &str = "0000000000000000000000000000000000000000";
+
+    #[test]
+    fn test_ref_update_parse_lines() {
+        let input = format!(
+            "aaa bbb refs/heads/main\n{NULL_SHA} ccc refs/heads/new\nddd {NULL_SHA} refs/heads/old\n"
+        );
+        let updates = RefUpdate::parse_lines(&input).unwrap();
+
+        assert_eq!(updates.len(), 3);
+        assert_eq!(updates[0].ref_name, "refs/heads/main");
+        assert_eq!(updates[0].revisions(), Some(vec!["aaa..bbb".to_string()]));
+
+        assert!(updates[1].is_create());
+        assert_eq!(
+            updates[1].revisions(),
+            Some(vec![
+                "ccc".to_string(),
+                "--not".to_string(),
+                "--all".to_string()
+            ])
+        );
+
+        assert!(updates[2].is_delete());
+        assert_eq!(updates[2].revisions(), None);
+
+        assert!(RefUpdate::parse_lines("aaa bbb\n").is_err());
+    }
+
+    #[test]
+    fn test_pre_receive_lints_new_commits() {
+        let input = format!("aaa bbb refs/heads/main\nddd {NULL_SHA} refs/heads/old\n");
+        let backend = FakeBackend::new().with_log(
+            "aaa..bbb",
+            vec![
+                CommitInfo {
+                    sha: "bbb".to_string(),
+                    parents: vec!["b0".to_string()],
+                    subject: "fix login".to_string(),
+                    ..Default::default()
+                },
+                CommitInfo {
+                    sha: "b0".to_string(),
+                    parents: vec!["aaa".to_string()],
+                    subject: ":memo: update docs".to_string(),
+                    ..Default::default()
+                },
+            ],
+        );
+
+        let results = GitHook::pre_receive(
+            &GitCommit::new(&backend),
+            &input,
+            &RangeLintOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(results.len(), 2);
+        let (update, report) = &results[0];
+        assert_eq!(update.ref_name, "refs/heads/main");
+        assert!(!report.is_ok());
+        assert_eq!(report.results.len(), 2);
+
+        // Deleted refs introduce no commits, so git log is only asked once
+        let (update, report) = &results[1];
+        assert!(update.is_delete());
+        assert!(report.is_ok());
+        assert!(report.results.is_empty());
+        assert_eq!(backend.calls(), vec!["log aaa..bbb"]);
+    }
+
+    fn temp_hooks_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("amoji-hooks-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_hook_kind_parse() {
+        for kind in HookKind::ALL {
+            assert_eq!(HookKind::parse(kind.file_name()), Some(kind));
+        }
+        assert_eq!(HookKind::parse("pre-push"), None);
+    }
+
+    #[test]
+    fn test_install_and_uninstall_fresh() {
+        let installer = HookInstaller::new(temp_hooks_dir("fresh"));
+        let kind = HookKind::PrepareCommitMsg;
+
+        assert_eq!(installer.status(kind).unwrap(), HookStatus::Missing);
+        assert_eq!(
+            installer.install(kind).unwrap(),
+            HookStatus::Installed { chained: false }
+        );
+
+        let script = fs::read_to_string(installer.hooks_dir().join("prepare-commit-msg")).unwrap();
+        assert!(script.starts_with("#!/bin/sh"));
+        assert!(script.contains("amoji hook prepare-commit-msg \"$@\""));
+
+        // Without amoji on PATH, the message is left alone
+        assert!(script.contains("command -v amoji >/dev/null 2>&1 || exit 0"));
+
+        // Installing twice is a no-op
+        assert_eq!(
+            installer.install(kind).unwrap(),
+            HookStatus::Installed { chained: false }
+        );
+
+        assert_eq!(installer.uninstall(kind).unwrap(), HookStatus::Missing);
+        fs::remove_dir_all(installer.hooks_dir()).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_commit_msg_hook_warns_without_amoji() {
+        let installer = HookInstaller::new(temp_hooks_dir("no-amoji"));
+        installer.install(HookKind::CommitMsg).unwrap();
+        let empty_path = installer.hooks_dir().join("empty-path");
+        fs::create_dir_all(&empty_path).unwrap();
+
+        let output = std::process::Command::new("/bin/sh")
+            .arg(installer.hooks_dir().join("commit-msg"))
+            .arg("COMMIT_EDITMSG")
+            .env("PATH", &empty_path)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("amoji: not found on PATH"));
+
+        fs::remove_dir_all(installer.hooks_dir()).unwrap();
+    }
+
+    #[test]
+    fn test_install_chains_existing_hook() {
+        let installer = HookInstaller::new(temp_hooks_dir("chain"));
+        let kind = HookKind::CommitMsg;
+        let existing = "#!/bin/sh\necho existing\n";
+
+        fs::create_dir_all(installer.hooks_dir()).unwrap();
+        fs::write(installer.hooks_dir().join("commit-msg"), existing).unwrap();
+        assert_eq!(installer.status(kind).unwrap(), HookStatus::Foreign);
+
+        assert_eq!(
+            installer.install(kind).unwrap(),
+            HookStatus::Installed { chained: true }
+        );
+        let chained = fs::read_to_string(installer.hooks_dir().join("commit-msg.amoji-chained"));
+        assert_eq!(chained.unwrap(), existing);
+
+        // Uninstall restores the original hook
+        assert_eq!(installer.uninstall(kind).unwrap(), HookStatus::Foreign);
+        let restored = fs::read_to_string(installer.hooks_dir().join("commit-msg")).unwrap();
+        assert_eq!(restored, existing);
+        assert!(
+            !installer
+                .hooks_dir()
+                .join("commit-msg.amoji-chained")
+                .exists()
+        );
+
+        // Uninstalling again leaves the foreign hook alone
+        assert_eq!(installer.uninstall(kind).unwrap(), HookStatus::Foreign);
+        fs::remove_dir_all(installer.hooks_dir()).unwrap();
+    }
+
+    #[test]
+    fn test_prepare_commit_msg_rewrites_file() {
+        let matcher = MatcherFactory::simple();
+        let path = std::env::temp_dir().join(format!(
+            "amoji-prepare-commit-msg-{}.txt",
+            std::process::id()
+        ));
+        fs::write(&path, "fix crash on startup\n\n# comment\n").unwrap();
+
+        let subject = GitHook::prepare_commit_msg(
+            matcher.as_ref(),
+            &path,
+            &MessageSource::Message,
+            &RepoState::default(),
+            '#',
+        )
+        .unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(subject, Some(":bug: fix crash on startup".to_string()));
+        assert_eq!(contents, ":bug: fix crash on startup\n\n# comment\n");
+    }
+}
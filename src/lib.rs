@@ -1,11 +1,36 @@
+pub mod backend;
+pub mod changelog;
 pub mod commit;
+pub mod config;
+pub mod diff;
 pub mod emoji;
+pub mod hook;
+pub mod lint;
+pub mod log;
 pub mod matcher;
+pub mod release;
+pub mod special;
+pub mod stats;
+pub mod status;
 
 // Re-export main types for convenience
-pub use commit::{GitCommit, GitError};
-pub use emoji::{EMOJI_MAP, EmojiLookup};
-pub use matcher::{GitmojiMatcher, MatcherFactory, MatcherResult};
+pub use backend::{FakeBackend, GitBackend, GitOutput, HookOutcome, ProcessBackend};
+pub use changelog::{Changelog, ChangelogEntry, ChangelogGroup, ChangelogSection};
+pub use commit::{CommitInfo, GitCommit, GitError};
+pub use config::{PathConfig, RepoConfig};
+pub use diff::{DiffAnalyzer, DiffSignal};
+pub use emoji::{EMOJI_MAP, EmojiLookup, SEMVER_MAP, SemverImpact};
+pub use hook::{GitHook, HookInstaller, HookKind, HookStatus, MessageSource};
+pub use lint::{
+    CommitLintResult, CommitLintStatus, CommitLinter, LintIssue, LintReport, RangeLintOptions,
+    RangeLintReport, SubjectGitmoji,
+};
+pub use log::{LogFilter, LogLine};
+pub use matcher::{Candidate, Explanation, GitmojiMatcher, MatcherFactory, MatcherResult};
+pub use release::{BumpReason, ReleasePlan, Version};
+pub use special::{RepoState, SpecialCommit};
+pub use stats::{AuthorUsage, CodeCount, Period, PeriodUsage, RepoStats, SectionCount, Usage};
+pub use status::{ConflictKind, EntryKind, FileState, StatusEntry, SubmoduleState};
 
 // Re-export LLM types only when the feature is enabled
 #[cfg(feature = "llm")]
@@ -82,6 +107,11 @@ mod tests {
         // Test GitCommit export
         let _commit = GitCommit::format_message(":sparkles:", "test");
 
+        // Test backend exports
+        let backend = FakeBackend::new();
+        let _git = GitCommit::new(&backend);
+        let _process: &dyn GitBackend = &ProcessBackend::new();
+
         // Test EmojiLookup export
         let _emoji = EmojiLookup::code_to_unicode(":sparkles:");
 
@@ -104,7 +134,8 @@ mod tests {
         assert!(invalid_emoji.is_none());
 
         // Test dry run commit (should always work)
-        let commit_result = GitCommit::commit("test message", true);
+        let backend = FakeBackend::new();
+        let commit_result = GitCommit::new(&backend).commit("test message", true);
         assert!(commit_result.is_ok());
 
         // Test matcher with edge cases
@@ -0,0 +1,526 @@
+use crate::{
+    commit::CommitInfo,
+    emoji::EmojiLookup,
+    hook::GitHook,
+    matcher::{GitmojiMatcher, simple::SimpleMatcher},
+};
+use anyhow::Result;
+use serde::Serialize;
+use std::{
+    fmt::{self, Write},
+    sync::LazyLock,
+};
+
+/// Subject prefixes used by `git rebase --autosquash`; these must never carry a gitmoji
+const AUTOSQUASH_PREFIXES: [&str; 3] = ["fixup! ", "squash! ", "amend! "];
+
+/// Matcher behind the suggestions, loaded once as a range can have many failing commits
+static SUGGESTION_MATCHER: LazyLock<SimpleMatcher> = LazyLock::new(SimpleMatcher::new);
+
+/// Subjects git writes itself for merge commits
+const MERGE_PREFIXES: [&str; 5] = [
+    "Merge branch ",
+    "Merge remote-tracking branch ",
+    "Merge tag ",
+    "Merge commit ",
+    "Merge pull request ",
+];
+
+/// Leading gitmoji of a commit subject
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubjectGitmoji<'a> {
+    /// A known gitmoji, in shortcode or Unicode form, followed by the rest of the subject
+    Valid { code: &'static str, text: &'a str },
+    /// Something shaped like a shortcode that isn't in the gitmoji list
+    Unknown { code: &'a str, text: &'a str },
+    /// No gitmoji at all
+    Missing,
+}
+
+impl<'a> SubjectGitmoji<'a> {
+    /// Parse the leading gitmoji of a commit subject
+    pub fn parse(subject: &'a str) -> Self {
+        if let Some((code, text)) = EmojiLookup::split_leading(subject) {
+            return SubjectGitmoji::Valid { code, text };
+        }
+
+        let trimmed = subject.trim_start();
+        if let Some(after_colon) = trimmed.strip_prefix(':')
+            && let Some(end) = after_colon.find(':')
+            && end > 0
+            && after_colon[..end]
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '+'))
+        {
+            let code = &trimmed[..end + 2];
+            return SubjectGitmoji::Unknown {
+                code,
+                text: trimmed[code.len()..].trim_start(),
+            };
+        }
+
+        SubjectGitmoji::Missing
+    }
+
+    /// The canonical emoji code, if the subject starts with a known gitmoji
+    pub fn code(&self) -> Option<&'static str> {
+        match self {
+            SubjectGitmoji::Valid { code, .. } => Some(code),
+            _ => None,
+        }
+    }
+}
+
+/// Why a commit subject was rejected
+#[derive(Debug, Clone, PartialEq)]
+pub enum LintIssue {
+    /// The message has no subject line
+    EmptySubject,
+    /// The subject doesn't start with a gitmoji
+    MissingGitmoji,
+    /// The subject starts with a shortcode that isn't a gitmoji
+    UnknownCode(String),
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintIssue::EmptySubject => write!(f, "commit message has no subject line"),
+            LintIssue::MissingGitmoji => write!(f, "subject does not start with a gitmoji"),
+            LintIssue::UnknownCode(code) => write!(f, "'{code}' is not a known gitmoji code"),
+        }
+    }
+}
+
+/// Result of linting a single commit subject
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintReport {
+    /// The subject line that was checked
+    pub subject: String,
+    /// The problem found, or None when the subject is valid
+    pub issue: Option<LintIssue>,
+    /// The emoji the simple matcher would have chosen, offered as a fix
+    pub suggestion: Option<String>,
+}
+
+impl LintReport {
+    /// Whether the subject passed the lint
+    pub fn is_ok(&self) -> bool {
+        self.issue.is_none()
+    }
+}
+
+/// Which commits a range lint should check
+#[derive(Debug, Clone, Default)]
+pub struct RangeLintOptions {
+    /// Also check merge commits
+    pub include_merges: bool,
+    /// Also check `fixup!`, `squash!` and `amend!` commits
+    pub include_fixups: bool,
+}
+
+/// Outcome for a single commit in a range lint
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommitLintStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// Lint result for a single commit
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CommitLintResult {
+    pub sha: String,
+    pub subject: String,
+    pub status: CommitLintStatus,
+    pub issue: Option<String>,
+    pub suggestion: Option<String>,
+}
+
+/// Lint results for a range of commits
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RangeLintReport {
+    pub results: Vec<CommitLintResult>,
+}
+
+impl RangeLintReport {
+    /// Number of commits with the given status
+    pub fn count(&self, status: CommitLintStatus) -> usize {
+        self.results.iter().filter(|r| r.status == status).count()
+    }
+
+    /// Whether no checked commit failed
+    pub fn is_ok(&self) -> bool {
+        self.count(CommitLintStatus::Failed) == 0
+    }
+
+    /// Render the report as JSON
+    pub fn to_json(&self) -> Result<String> {
+        let value = serde_json::json!({
+            "checked": self.count(CommitLintStatus::Passed) + self.count(CommitLintStatus::Failed),
+            "failed": self.count(CommitLintStatus::Failed),
+            "skipped": self.count(CommitLintStatus::Skipped),
+            "commits": self.results,
+        });
+        Ok(serde_json::to_string_pretty(&value)?)
+    }
+
+    /// Render the report as JUnit XML, one test case per commit
+    pub fn to_junit(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<testsuite name=\"amoji lint\" tests=\"{}\" failures=\"{}\" skipped=\"{}\">",
+            self.results.len(),
+            self.count(CommitLintStatus::Failed),
+            self.count(CommitLintStatus::Skipped),
+        );
+
+        for result in &self.results {
+            let _ = write!(
+                xml,
+                "  <testcase classname=\"commit\" name=\"{}\"",
+                xml_escape(&format!("{} {}", result.sha, result.subject))
+            );
+            match result.status {
+                CommitLintStatus::Passed => xml.push_str("/>\n"),
+                CommitLintStatus::Skipped => xml.push_str(">\n    <skipped/>\n  </testcase>\n"),
+                CommitLintStatus::Failed => {
+                    let issue = result.issue.as_deref().unwrap_or_default();
+                    let mut body = format!("{}\n", result.subject);
+                    if let Some(suggestion) = &result.suggestion {
+                        let _ = writeln!(body, "suggestion: {suggestion}");
+                    }
+                    let _ = write!(
+                        xml,
+                        ">\n    <failure message=\"{}\">{}</failure>\n  </testcase>\n",
+                        xml_escape(issue),
+                        xml_escape(&body)
+                    );
+                }
+            }
+        }
+
+        xml.push_str("</testsuite>\n");
+        xml
+    }
+}
+
+/// Escape text for use in XML attributes and content
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters are not allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\n' | '\t') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Gitmoji linting for commit messages
+pub struct CommitLinter;
+
+impl CommitLinter {
+    /// Lint a single subject line
+    pub fn lint_subject(subject: &str) -> Result<LintReport> {
+        if Self::is_exempt(subject.trim()) {
+            return Ok(LintReport {
+                subject: subject.trim().to_string(),
+                issue: None,
+                suggestion: None,
+            });
+        }
+
+        Self::check_subject(subject)
+    }
+
+    /// Lint a single subject line without any exemptions
+    fn check_subject(subject: &str) -> Result<LintReport> {
+        let subject = subject.trim();
+
+        let issue = if subject.is_empty() {
+            Some(LintIssue::EmptySubject)
+        } else {
+            match SubjectGitmoji::parse(subject) {
+                SubjectGitmoji::Valid { .. } => None,
+                SubjectGitmoji::Unknown { code, .. } => Some(LintIssue::UnknownCode(code.into())),
+                SubjectGitmoji::Missing => Some(LintIssue::MissingGitmoji),
+            }
+        };
+
+        let suggestion = match &issue {
+            Some(LintIssue::MissingGitmoji) => Self::suggest(subject)?,
+            Some(LintIssue::UnknownCode(_)) => match SubjectGitmoji::parse(subject) {
+                SubjectGitmoji::Unknown { text, .. } => Self::suggest(text)?,
+                _ => None,
+            },
+            _ => None,
+        };
+
+        Ok(LintReport {
+            subject: subject.to_string(),
+            issue,
+            suggestion,
+        })
+    }
+
+    /// Lint a full commit message, as found in the commit-msg hook's message file
+    pub fn lint_message(contents: &str, comment_char: char) -> Result<LintReport> {
+        let subject = GitHook::subject_line(contents, comment_char)
+            .map(|(_, subject)| subject)
+            .unwrap_or_default();
+        Self::lint_subject(subject)
+    }
+
+    /// Subjects generated by git that are allowed without a gitmoji
+    pub fn is_exempt(subject: &str) -> bool {
+        Self::is_autosquash(subject) || MERGE_PREFIXES.iter().any(|p| subject.starts_with(p))
+    }
+
+    /// Whether the subject marks a commit for `git rebase --autosquash`
+    pub fn is_autosquash(subject: &str) -> bool {
+        AUTOSQUASH_PREFIXES.iter().any(|p| subject.starts_with(p))
+    }
+
+    /// Lint every commit in a list, such as the output of `GitCommit::log`
+    pub fn lint_commits(
+        commits: &[CommitInfo],
+        options: &RangeLintOptions,
+    ) -> Result<RangeLintReport> {
+        let results = commits
+            .iter()
+            .map(|commit| {
+                let skipped = (commit.is_merge() && !options.include_merges)
+                    || (Self::is_autosquash(&commit.subject) && !options.include_fixups);
+
+                if skipped {
+                    return Ok(CommitLintResult {
+                        sha: commit.sha.clone(),
+                        subject: commit.subject.clone(),
+                        status: CommitLintStatus::Skipped,
+                        issue: None,
+                        suggestion: None,
+                    });
+                }
+
+                let report = Self::check_subject(&commit.subject)?;
+                Ok(CommitLintResult {
+                    sha: commit.sha.clone(),
+                    subject: commit.subject.clone(),
+                    status: if report.is_ok() {
+                        CommitLintStatus::Passed
+                    } else {
+                        CommitLintStatus::Failed
+                    },
+                    issue: report.issue.map(|issue| issue.to_string()),
+                    suggestion: report.suggestion,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(RangeLintReport { results })
+    }
+
+    /// Ask the simple matcher which emoji it would have used for this subject
+    fn suggest(text: &str) -> Result<Option<String>> {
+        Ok(SUGGESTION_MATCHER
+            .match_emoji(text)?
+            .map(|(_, formatted_message)| formatted_message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_subject_gitmoji() {
+        assert_eq!(
+            SubjectGitmoji::parse(":bug: fix login"),
+            SubjectGitmoji::Valid {
+                code: ":bug:",
+                text: "fix login"
+            }
+        );
+        assert_eq!(
+            SubjectGitmoji::parse("✨ add search"),
+            SubjectGitmoji::Valid {
+                code: ":sparkles:",
+                text: "add search"
+            }
+        );
+        assert_eq!(
+            SubjectGitmoji::parse(":bugfix: fix login"),
+            SubjectGitmoji::Unknown {
+                code: ":bugfix:",
+                text: "fix login"
+            }
+        );
+        assert_eq!(SubjectGitmoji::parse("fix login"), SubjectGitmoji::Missing);
+        assert_eq!(SubjectGitmoji::parse("fix: login"), SubjectGitmoji::Missing);
+        assert_eq!(SubjectGitmoji::parse(": login"), SubjectGitmoji::Missing);
+    }
+
+    #[test]
+    fn test_lint_subject_valid() {
+        for subject in [
+            ":sparkles: add search",
+            "🐛 fix login",
+            "⚡ speed up parser",
+        ] {
+            let report = CommitLinter::lint_subject(subject).unwrap();
+            assert!(report.is_ok(), "Should accept: '{subject}'");
+            assert!(report.suggestion.is_none());
+        }
+    }
+
+    #[test]
+    fn test_lint_subject_missing_gitmoji() {
+        let report = CommitLinter::lint_subject("fix login bug").unwrap();
+
+        assert_eq!(report.issue, Some(LintIssue::MissingGitmoji));
+        assert_eq!(report.suggestion, Some(":bug: fix login bug".to_string()));
+    }
+
+    #[test]
+    fn test_lint_subject_unknown_code() {
+        let report = CommitLinter::lint_subject(":bugfix: fix login bug").unwrap();
+
+        assert_eq!(
+            report.issue,
+            Some(LintIssue::UnknownCode(":bugfix:".to_string()))
+        );
+        assert_eq!(report.suggestion, Some(":bug: fix login bug".to_string()));
+    }
+
+    #[test]
+    fn test_lint_subject_exempt() {
+        for subject in [
+            "fixup! :bug: fix login",
+            "squash! add search",
+            "Merge branch 'feature' into main",
+            "Merge pull request #12 from user/branch",
+        ] {
+            let report = CommitLinter::lint_subject(subject).unwrap();
+            assert!(report.is_ok(), "Should exempt: '{subject}'");
+        }
+    }
+
+    #[test]
+    fn test_lint_message() {
+        let contents = "# comment\n\n:memo: update readme\n\nbody\n";
+        assert!(CommitLinter::lint_message(contents, '#').unwrap().is_ok());
+
+        let report = CommitLinter::lint_message("# only comments\n", '#').unwrap();
+        assert_eq!(report.issue, Some(LintIssue::EmptySubject));
+
+        let report = CommitLinter::lint_message("update readme\n", '#').unwrap();
+        assert_eq!(report.issue, Some(LintIssue::MissingGitmoji));
+    }
+
+    fn commit(sha: &str, parents: usize, subject: &str) -> CommitInfo {
+        CommitInfo {
+            sha: sha.to_string(),
+            parents: (0..parents).map(|i| format!("parent{i}")).collect(),
+            subject: subject.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn sample_commits() -> Vec<CommitInfo> {
+        vec![
+            commit("a1", 1, ":sparkles: add search"),
+            commit("b2", 1, "fix login <bug> & crash"),
+            commit("c3", 2, "Merge branch 'feature'"),
+            commit("d4", 1, "fixup! add search"),
+            commit("e5", 1, ":bugfix: typo"),
+        ]
+    }
+
+    #[test]
+    fn test_lint_commits_default_skips() {
+        let report =
+            CommitLinter::lint_commits(&sample_commits(), &RangeLintOptions::default()).unwrap();
+
+        let statuses: Vec<_> = report.results.iter().map(|r| r.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                CommitLintStatus::Passed,
+                CommitLintStatus::Failed,
+                CommitLintStatus::Skipped,
+                CommitLintStatus::Skipped,
+                CommitLintStatus::Failed,
+            ]
+        );
+        assert!(!report.is_ok());
+        assert_eq!(
+            report.results[4].issue.as_deref(),
+            Some("':bugfix:' is not a known gitmoji code")
+        );
+    }
+
+    #[test]
+    fn test_lint_commits_include_all() {
+        let options = RangeLintOptions {
+            include_merges: true,
+            include_fixups: true,
+        };
+        let report = CommitLinter::lint_commits(&sample_commits(), &options).unwrap();
+
+        assert_eq!(report.count(CommitLintStatus::Skipped), 0);
+        assert_eq!(report.count(CommitLintStatus::Failed), 4);
+    }
+
+    #[test]
+    fn test_range_report_json() {
+        let report =
+            CommitLinter::lint_commits(&sample_commits(), &RangeLintOptions::default()).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+
+        assert_eq!(json["checked"], 3);
+        assert_eq!(json["failed"], 2);
+        assert_eq!(json["skipped"], 2);
+        assert_eq!(json["commits"][1]["sha"], "b2");
+        assert_eq!(json["commits"][1]["status"], "failed");
+        assert_eq!(
+            json["commits"][1]["suggestion"],
+            ":bug: fix login <bug> & crash"
+        );
+    }
+
+    #[test]
+    fn test_range_report_junit() {
+        let report =
+            CommitLinter::lint_commits(&sample_commits(), &RangeLintOptions::default()).unwrap();
+        let xml = report.to_junit();
+
+        assert!(xml.starts_with("<?xml"));
+        assert!(xml.contains("tests=\"5\" failures=\"2\" skipped=\"2\""));
+        assert!(xml.contains("name=\"a1 :sparkles: add search\"/>"));
+        assert!(xml.contains("fix login &lt;bug&gt; &amp; crash"));
+        assert!(xml.contains("<skipped/>"));
+        assert!(xml.contains("<failure message=\"subject does not start with a gitmoji\">"));
+        assert!(xml.trim_end().ends_with("</testsuite>"));
+    }
+
+    #[test]
+    fn test_lint_issue_display() {
+        assert_eq!(
+            LintIssue::MissingGitmoji.to_string(),
+            "subject does not start with a gitmoji"
+        );
+        assert_eq!(
+            LintIssue::UnknownCode(":nope:".to_string()).to_string(),
+            "':nope:' is not a known gitmoji code"
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{
+    changelog::ChangelogSection,
+    commit::CommitInfo,
+    emoji::{EMOJI_MAP, EmojiLookup, SemverImpact},
+    lint::SubjectGitmoji,
+    release::ReleasePlan,
+};
+
+/// Which commits `amoji log` shows
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LogFilter {
+    /// Only commits with this gitmoji
+    pub emoji: Option<&'static str>,
+    /// Only commits with this semver impact
+    pub semver: Option<SemverImpact>,
+}
+
+impl LogFilter {
+    /// Resolve a gitmoji given as a name ("bug"), a shortcode (":bug:") or a glyph ("🐛")
+    pub fn parse_emoji(name: &str) -> Option<&'static str> {
+        let name = name.trim();
+        let code = format!(":{}:", name.trim_matches(':'));
+        if let Some((code, _)) = EMOJI_MAP.get_key_value(code.as_str()) {
+            return Some(code);
+        }
+        EmojiLookup::split_leading(name)
+            .filter(|(_, rest)| rest.is_empty())
+            .map(|(code, _)| code)
+    }
+
+    /// Whether a commit passes every filter that is set
+    pub fn matches(&self, commit: &CommitInfo) -> bool {
+        let emoji_matches = self
+            .emoji
+            .is_none_or(|emoji| SubjectGitmoji::parse(&commit.subject).code() == Some(emoji));
+        let semver_matches = self
+            .semver
+            .is_none_or(|semver| ReleasePlan::commit_impact(&commit.message()) == Some(semver));
+        emoji_matches && semver_matches
+    }
+}
+
+/// A commit subject with its gitmoji resolved for display
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogLine<'a> {
+    pub short_sha: &'a str,
+    /// The Unicode glyph of the gitmoji, None when the subject has no known gitmoji
+    pub emoji: Option<&'static str>,
+    /// The subject without its gitmoji
+    pub text: &'a str,
+    /// Changelog section of the gitmoji, used to pick a color
+    pub section: ChangelogSection,
+}
+
+impl<'a> LogLine<'a> {
+    pub fn from_commit(commit: &'a CommitInfo) -> Self {
+        let (code, text) = match SubjectGitmoji::parse(&commit.subject) {
+            SubjectGitmoji::Valid { code, text } => (Some(code), text),
+            _ => (None, commit.subject.as_str()),
+        };
+        Self {
+            short_sha: commit.short_sha(),
+            emoji: code.and_then(EmojiLookup::code_to_unicode),
+            text,
+            section: ChangelogSection::for_code(code),
+        }
+    }
+
+    /// The subject with its shortcode replaced by the glyph
+    pub fn subject(&self) -> String {
+        match self.emoji {
+            Some(emoji) => format!("{emoji} {}", self.text),
+            None => self.text.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(subject: &str) -> CommitInfo {
+        CommitInfo {
+            sha: "abcdef0123".to_string(),
+            subject: subject.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_emoji() {
+        assert_eq!(LogFilter::parse_emoji("bug"), Some(":bug:"));
+        assert_eq!(LogFilter::parse_emoji(":sparkles:"), Some(":sparkles:"));
+        assert_eq!(LogFilter::parse_emoji("🐛"), Some(":bug:"));
+        assert_eq!(LogFilter::parse_emoji("♻"), Some(":recycle:"));
+        assert_eq!(LogFilter::parse_emoji("nope"), None);
+        assert_eq!(LogFilter::parse_emoji("🐛 fix"), None);
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let bug = LogFilter {
+            emoji: Some(":bug:"),
+            ..Default::default()
+        };
+        assert!(bug.matches(&commit(":bug: fix crash")));
+        assert!(bug.matches(&commit("🐛 fix crash")));
+        assert!(!bug.matches(&commit(":sparkles: add log")));
+        assert!(!bug.matches(&commit("fix crash")));
+
+        let minor = LogFilter {
+            semver: Some(SemverImpact::Minor),
+            ..Default::default()
+        };
+        assert!(minor.matches(&commit(":sparkles: add log")));
+        assert!(!minor.matches(&commit(":bug: fix crash")));
+        assert!(!minor.matches(&commit(":memo: update docs")));
+
+        let major = LogFilter {
+            semver: Some(SemverImpact::Major),
+            ..Default::default()
+        };
+        assert!(major.matches(&commit(":sparkles: feat(api)!: drop v1")));
+
+        assert!(LogFilter::default().matches(&commit("anything")));
+    }
+
+    #[test]
+    fn test_log_line() {
+        let with_code = commit(":sparkles: add log viewer");
+        let line = LogLine::from_commit(&with_code);
+        assert_eq!(line.short_sha, "abcdef0");
+        assert_eq!(line.subject(), "✨ add log viewer");
+        assert_eq!(line.section, ChangelogSection::Features);
+
+        let plain = commit("Merge branch 'topic'");
+        let line = LogLine::from_commit(&plain);
+        assert_eq!(line.emoji, None);
+        assert_eq!(line.subject(), "Merge branch 'topic'");
+        assert_eq!(line.section, ChangelogSection::Other);
+
+        let unknown = commit(":nope: keep as is");
+        assert_eq!(
+            LogLine::from_commit(&unknown).subject(),
+            ":nope: keep as is"
+        );
+    }
+}
@@ -1,8 +1,27 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 #[cfg(feature = "llm")]
 use auto_gitmoji::matcher::llm::{LLMConfig, LLMModel, LLMProvider};
-use auto_gitmoji::{commit::GitCommit, emoji::EmojiLookup, matcher::MatcherFactory};
-use clap::Parser;
+use auto_gitmoji::{
+    backend::{GitBackend, ProcessBackend},
+    changelog::Changelog,
+    changelog::ChangelogSection,
+    commit::{GitCommit, GitError},
+    diff::{DiffAnalyzer, DiffSignal, summary::DEFAULT_BUDGET},
+    emoji::{EmojiLookup, SemverImpact},
+    hook::{GitHook, HookInstaller, HookKind, HookStatus, MessageSource},
+    lint::{CommitLintStatus, CommitLinter, LintReport, RangeLintOptions},
+    log::{LogFilter, LogLine},
+    matcher::{Explanation, GenerationContext, GitmojiMatcher, MatcherFactory},
+    release::{self, ReleasePlan},
+    special::{RepoState, SpecialCommit},
+    stats::{Period, RepoStats},
+};
+use clap::{Parser, Subcommand};
+use std::{
+    ffi::OsString,
+    io::{IsTerminal, Read},
+    path::{Path, PathBuf},
+};
 
 // ANSI color codes for terminal formatting
 const RESET: &str = "\x1b[0m";
@@ -23,9 +42,13 @@ const BRIGHT_YELLOW: &str = "\x1b[93m";
     name = "amoji",
     version = "0.1.0",
     author = "wty92911",
-    about = "Automatically prepend gitmoji to commit messages"
+    about = "Automatically prepend gitmoji to commit messages",
+    args_conflicts_with_subcommands = true
 )]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// The commit message
     message: Option<String>,
 
@@ -46,6 +69,240 @@ struct Args {
     #[arg(short = 'm')]
     #[arg(default_value_t = false)]
     help_message: bool,
+
+    /// Write the whole commit message from the staged diff, using the message as a hint
+    #[arg(long)]
+    generate: bool,
+
+    /// Print how the gitmoji was chosen instead of committing
+    #[arg(long, conflicts_with = "generate")]
+    explain: bool,
+
+    /// Size of the staged diff sent when generating a message, in bytes
+    #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_BUDGET, requires = "generate")]
+    diff_budget: usize,
+
+    /// Run as if amoji was started in <PATH> instead of the current directory
+    #[arg(short = 'C', long = "repo", value_name = "PATH", global = true)]
+    repo: Option<PathBuf>,
+
+    /// Extra arguments forwarded to `git commit` unchanged (after `--`)
+    #[arg(last = true)]
+    git_args: Vec<String>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run as a git hook
+    Hook {
+        #[command(subcommand)]
+        hook: HookCommand,
+    },
+    /// Check that a commit message starts with a valid gitmoji
+    Lint(LintArgs),
+    /// Write a whole commit message from the staged diff and commit it
+    Generate(GenerateArgs),
+    /// Write a changelog grouped by gitmoji category
+    Changelog(ChangelogArgs),
+    /// Suggest the next version from the gitmojis since the latest release tag
+    Bump(BumpArgs),
+    /// Show gitmoji usage per author and over time
+    Stats(StatsArgs),
+    /// Show git log with gitmojis rendered as emoji
+    Log(LogArgs),
+}
+
+#[derive(clap::Args)]
+struct LogArgs {
+    /// Only show commits with this gitmoji, e.g. bug, :bug: or 🐛
+    #[arg(long, value_parser = parse_emoji_filter)]
+    emoji: Option<&'static str>,
+
+    /// Only show commits with this semver impact
+    #[arg(long, value_enum)]
+    semver: Option<SemverFilter>,
+
+    /// Arguments forwarded to `git log`, e.g. -n 20 main or -- src/
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    git_args: Vec<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+enum SemverFilter {
+    Major,
+    Minor,
+    Patch,
+}
+
+fn parse_emoji_filter(name: &str) -> Result<&'static str, String> {
+    LogFilter::parse_emoji(name).ok_or_else(|| format!("unknown gitmoji '{name}'"))
+}
+
+#[derive(clap::Args)]
+struct StatsArgs {
+    /// Revision range to scan, e.g. v1.0.0..HEAD
+    #[arg(long, default_value = "HEAD")]
+    range: String,
+
+    /// Length of the trend periods
+    #[arg(long, value_enum, default_value_t = TrendPeriod::Month)]
+    by: TrendPeriod,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = StatsFormat::Table)]
+    format: StatsFormat,
+}
+
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+enum TrendPeriod {
+    Week,
+    Month,
+}
+
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+enum StatsFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(clap::Args)]
+struct BumpArgs {
+    /// Also set the version in this Cargo.toml
+    #[arg(long, value_name = "PATH")]
+    write: Option<PathBuf>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = BumpFormat::Text)]
+    format: BumpFormat,
+}
+
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+enum BumpFormat {
+    Text,
+    Json,
+}
+
+#[derive(clap::Args)]
+struct ChangelogArgs {
+    /// Start after this revision, defaults to the latest tag
+    #[arg(long)]
+    from: Option<String>,
+
+    /// End at this revision
+    #[arg(long, default_value = "HEAD")]
+    to: String,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = ChangelogFormat::Markdown)]
+    format: ChangelogFormat,
+}
+
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+enum ChangelogFormat {
+    Markdown,
+    Json,
+    KeepAChangelog,
+}
+
+#[derive(clap::Args)]
+struct GenerateArgs {
+    /// A short description of the change to guide the message
+    hint: Option<String>,
+
+    /// Show what would be committed without actually committing
+    #[arg(long, short)]
+    dry_run: bool,
+
+    /// Only print the generated message, don't commit
+    #[arg(long)]
+    print: bool,
+
+    /// Size of the staged diff sent to the LLM, in bytes
+    #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_BUDGET)]
+    diff_budget: usize,
+
+    /// Extra arguments forwarded to `git commit` unchanged (after `--`)
+    #[arg(last = true)]
+    git_args: Vec<String>,
+}
+
+#[derive(clap::Args)]
+struct LintArgs {
+    /// The commit subject to check
+    message: Option<String>,
+
+    /// Read the commit message from a file instead
+    #[arg(long, conflicts_with_all = ["message", "range"])]
+    file: Option<PathBuf>,
+
+    /// Check every commit in a revision range, e.g. origin/main..HEAD
+    #[arg(long, conflicts_with = "message")]
+    range: Option<String>,
+
+    /// Report format for range checks
+    #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+    format: ReportFormat,
+
+    /// Also check merge commits in the range
+    #[arg(long)]
+    include_merges: bool,
+
+    /// Also check fixup!/squash!/amend! commits in the range
+    #[arg(long)]
+    include_fixups: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+enum ReportFormat {
+    Text,
+    Json,
+    Junit,
+}
+
+#[derive(Subcommand)]
+enum HookCommand {
+    /// Emojify the subject of the commit message file in place
+    PrepareCommitMsg {
+        /// Path to the commit message file
+        file: PathBuf,
+        /// Source of the commit message (message, template, merge, squash or commit)
+        source: Option<String>,
+        /// Commit object name, given for the commit source
+        sha: Option<String>,
+    },
+    /// Reject commit messages that don't start with a valid gitmoji
+    CommitMsg {
+        /// Path to the commit message file
+        file: PathBuf,
+    },
+    /// Reject pushes whose new commits lack a valid gitmoji (server side)
+    PreReceive {
+        /// Also check merge commits
+        #[arg(long)]
+        include_merges: bool,
+        /// Also check fixup!/squash!/amend! commits
+        #[arg(long)]
+        include_fixups: bool,
+    },
+    /// Install amoji's hooks, chaining to any existing hooks
+    Install {
+        /// Hooks to install
+        #[arg(long = "hook", value_parser = parse_hook_kind, default_values = ["prepare-commit-msg", "commit-msg"])]
+        hooks: Vec<HookKind>,
+    },
+    /// Remove amoji's hooks and restore the hooks they chained to
+    Uninstall {
+        /// Hooks to remove
+        #[arg(long = "hook", value_parser = parse_hook_kind, default_values = ["prepare-commit-msg", "commit-msg"])]
+        hooks: Vec<HookKind>,
+    },
+    /// Show which hooks are installed
+    Status,
+}
+
+fn parse_hook_kind(name: &str) -> Result<HookKind, String> {
+    HookKind::parse(name).ok_or_else(|| format!("unsupported hook '{name}'"))
 }
 
 fn print_help_message() {
@@ -82,8 +339,478 @@ fn print_help_message() {
     );
 }
 
+/// Create the configured matcher: LLM with fallback when the feature is enabled, simple otherwise
+fn create_matcher() -> Result<Box<dyn GitmojiMatcher>> {
+    if cfg!(feature = "llm") {
+        #[cfg(feature = "llm")]
+        {
+            // just SiliconFlow is supported now
+            Ok(MatcherFactory::llm_with_fallback(LLMConfig::from_env(
+                LLMProvider::SiliconFlow,
+                LLMModel::Qwen2_7bInstruct,
+            )?))
+        }
+        #[cfg(not(feature = "llm"))]
+        {
+            Ok(MatcherFactory::simple())
+        }
+    } else {
+        Ok(MatcherFactory::simple())
+    }
+}
+
+/// Signals from the staged changes, empty outside a repository
+/// Failing to inspect the changes only costs precision, so it is reported but not fatal
+fn staged_signals(git: &GitCommit) -> Vec<DiffSignal> {
+    if !git.is_git_repository().unwrap_or(false) {
+        return Vec::new();
+    }
+
+    DiffAnalyzer::staged_signals(git).unwrap_or_else(|e| {
+        eprintln!("{YELLOW}⚠️  Could not inspect staged changes: {e:#}{RESET}");
+        Vec::new()
+    })
+}
+
+fn run_hook(hook: HookCommand, git: &GitCommit) -> Result<()> {
+    match hook {
+        HookCommand::PrepareCommitMsg { file, source, .. } => {
+            let source = MessageSource::parse(source.as_deref());
+            let result = create_matcher().and_then(|matcher| {
+                let contents = std::fs::read_to_string(&file)?;
+                let message = GitHook::message_text(&contents, git.comment_char());
+                let mut signals = DiffAnalyzer::message_signals(&message);
+                signals.extend(staged_signals(git));
+
+                let matcher = MatcherFactory::diff_aware(matcher, signals);
+                let state = git.repo_state()?;
+                GitHook::prepare_commit_msg(
+                    matcher.as_ref(),
+                    &file,
+                    &source,
+                    &state,
+                    git.comment_char(),
+                )
+            });
+
+            // Never block the commit because of amoji, just leave the message untouched
+            if let Err(e) = result {
+                eprintln!("{YELLOW}⚠️  amoji: could not emojify commit message: {e}{RESET}");
+            }
+            Ok(())
+        }
+        HookCommand::CommitMsg { file } => {
+            let contents = std::fs::read_to_string(&file)?;
+            let report = CommitLinter::lint_message(&contents, git.comment_char())?;
+            if !report.is_ok() {
+                print_lint_report(&report);
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        HookCommand::PreReceive {
+            include_merges,
+            include_fixups,
+        } => {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input)?;
+
+            let options = RangeLintOptions {
+                include_merges,
+                include_fixups,
+            };
+            let results = GitHook::pre_receive(git, &input, &options)?;
+
+            let mut rejected = false;
+            for (update, report) in &results {
+                for result in &report.results {
+                    if result.status != CommitLintStatus::Failed {
+                        continue;
+                    }
+                    rejected = true;
+                    let short_sha = &result.sha[..result.sha.len().min(7)];
+                    eprintln!(
+                        "{RED}✖{RESET} {BOLD}{}{RESET} {short_sha} {}",
+                        update.ref_name, result.subject
+                    );
+                    if let Some(issue) = &result.issue {
+                        eprintln!("    {issue}");
+                    }
+                    if let Some(suggestion) = &result.suggestion {
+                        eprintln!("    suggestion: {suggestion}");
+                    }
+                }
+            }
+
+            if rejected {
+                eprintln!(
+                    "{RED}❌ Push rejected: every new commit must start with a gitmoji{RESET}"
+                );
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        HookCommand::Install { hooks } => {
+            let installer = HookInstaller::new(git.hooks_dir()?);
+            for kind in hooks {
+                let status = installer.install(kind)?;
+                println!(
+                    "{BRIGHT_GREEN}✅ Installed{RESET} {BOLD}{}{RESET} {DIM}({}){RESET}",
+                    kind.file_name(),
+                    describe_hook_status(&status)
+                );
+            }
+            Ok(())
+        }
+        HookCommand::Uninstall { hooks } => {
+            let installer = HookInstaller::new(git.hooks_dir()?);
+            for kind in hooks {
+                match installer.status(kind)? {
+                    HookStatus::Installed { chained } => {
+                        installer.uninstall(kind)?;
+                        let note = if chained {
+                            " (previous hook restored)"
+                        } else {
+                            ""
+                        };
+                        println!(
+                            "{BRIGHT_GREEN}✅ Removed{RESET} {BOLD}{}{RESET}{DIM}{note}{RESET}",
+                            kind.file_name()
+                        );
+                    }
+                    status => println!(
+                        "{DIM}Skipped {} ({}){RESET}",
+                        kind.file_name(),
+                        describe_hook_status(&status)
+                    ),
+                }
+            }
+            Ok(())
+        }
+        HookCommand::Status => {
+            let installer = HookInstaller::new(git.hooks_dir()?);
+            println!(
+                "{BOLD}{CYAN}Hooks directory:{RESET} {}",
+                installer.hooks_dir().display()
+            );
+            for kind in HookKind::ALL {
+                let status = installer.status(kind)?;
+                println!(
+                    "  {BOLD}{}{RESET}: {}",
+                    kind.file_name(),
+                    describe_hook_status(&status)
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+fn run_lint(args: LintArgs, git: &GitCommit) -> Result<()> {
+    if let Some(range) = &args.range {
+        return run_range_lint(range, &args, git);
+    }
+
+    let report = match (args.message, args.file) {
+        (_, Some(file)) => {
+            let contents = std::fs::read_to_string(&file)?;
+            CommitLinter::lint_message(&contents, git.comment_char())?
+        }
+        (Some(message), None) => CommitLinter::lint_subject(&message)?,
+        (None, None) => {
+            return Err(anyhow::anyhow!(
+                "Message, --file or --range argument is required"
+            ));
+        }
+    };
+
+    if report.is_ok() {
+        println!("{BRIGHT_GREEN}✅ Valid gitmoji commit message{RESET}");
+        return Ok(());
+    }
+
+    print_lint_report(&report);
+    std::process::exit(1);
+}
+
+fn run_changelog(args: ChangelogArgs, git: &GitCommit) -> Result<()> {
+    let from = match args.from {
+        Some(from) => Some(from),
+        None => git.latest_tag(&args.to)?,
+    };
+    let range = match &from {
+        Some(from) => format!("{from}..{}", args.to),
+        None => args.to.clone(),
+    };
+
+    let changelog = Changelog::from_commits(&git.log(&range)?, from.as_deref(), &args.to);
+    match args.format {
+        ChangelogFormat::Markdown => print!("{}", changelog.to_markdown()),
+        ChangelogFormat::Json => println!("{}", changelog.to_json()?),
+        ChangelogFormat::KeepAChangelog => print!("{}", changelog.to_keep_a_changelog()),
+    }
+    Ok(())
+}
+
+fn run_log(args: LogArgs, git: &GitCommit) -> Result<()> {
+    let filter = LogFilter {
+        emoji: args.emoji,
+        semver: args.semver.map(|semver| match semver {
+            SemverFilter::Major => SemverImpact::Major,
+            SemverFilter::Minor => SemverImpact::Minor,
+            SemverFilter::Patch => SemverImpact::Patch,
+        }),
+    };
+    let revisions: Vec<&str> = args.git_args.iter().map(String::as_str).collect();
+    let commits = git.log_revisions(&revisions)?;
+
+    // Like git, only color output meant for a person
+    let color = std::io::stdout().is_terminal();
+    for commit in commits.iter().filter(|commit| filter.matches(commit)) {
+        let line = LogLine::from_commit(commit);
+        if color {
+            let section_color = section_color(line.section);
+            println!(
+                "{YELLOW}{}{RESET} {section_color}{}{RESET}",
+                line.short_sha,
+                line.subject()
+            );
+        } else {
+            println!("{} {}", line.short_sha, line.subject());
+        }
+    }
+    Ok(())
+}
+
+/// Color of a log line, by the changelog section of its gitmoji
+fn section_color(section: ChangelogSection) -> &'static str {
+    match section {
+        ChangelogSection::Breaking => RED,
+        ChangelogSection::Features => BRIGHT_GREEN,
+        ChangelogSection::Fixes => BRIGHT_YELLOW,
+        ChangelogSection::Security => MAGENTA,
+        ChangelogSection::Performance => BRIGHT_CYAN,
+        ChangelogSection::Dependencies => BLUE,
+        ChangelogSection::Docs => CYAN,
+        ChangelogSection::Refactoring => GREEN,
+        ChangelogSection::Tests | ChangelogSection::Build => DIM,
+        ChangelogSection::Other => "",
+    }
+}
+
+fn run_stats(args: StatsArgs, git: &GitCommit) -> Result<()> {
+    let period = match args.by {
+        TrendPeriod::Week => Period::Week,
+        TrendPeriod::Month => Period::Month,
+    };
+    let stats = RepoStats::from_commits(&git.log(&args.range)?, &args.range, period);
+    match args.format {
+        StatsFormat::Table => print!("{}", stats.to_table()),
+        StatsFormat::Json => println!("{}", stats.to_json()?),
+        StatsFormat::Csv => print!("{}", stats.to_csv()),
+    }
+    Ok(())
+}
+
+fn run_bump(args: BumpArgs, repo: Option<&Path>, git: &GitCommit) -> Result<()> {
+    let tags = git.tags("HEAD")?;
+    let latest = ReleasePlan::latest_release(&tags);
+    let range = match latest {
+        Some((tag, _)) => format!("{tag}..HEAD"),
+        None => "HEAD".to_string(),
+    };
+    let plan = ReleasePlan::from_commits(latest, &git.log(&range)?);
+
+    match args.format {
+        BumpFormat::Json => println!("{}", serde_json::to_string_pretty(&plan)?),
+        BumpFormat::Text => {
+            let tag = plan.tag.as_deref().unwrap_or("none");
+            println!("{BOLD}{CYAN}🔖 Latest release:{RESET} {tag}");
+
+            let Some(impact) = plan.impact else {
+                println!(
+                    "{BRIGHT_GREEN}✅ No release needed:{RESET} no commit since {tag} changes the version"
+                );
+                return Ok(());
+            };
+            println!(
+                "{BOLD}{GREEN}🚀 Next version:{RESET} {} {DIM}({}){RESET}\n",
+                plan.next_tag(),
+                impact.as_str()
+            );
+            for reason in &plan.commits {
+                println!(
+                    "  {DIM}{}{RESET} {} {DIM}({}){RESET}",
+                    &reason.sha[..reason.sha.len().min(7)],
+                    reason.subject,
+                    reason.impact.as_str()
+                );
+            }
+        }
+    }
+
+    if let Some(path) = args.write
+        && plan.impact.is_some()
+    {
+        // Relative paths follow -C like git does
+        let path = repo.map_or(path.clone(), |repo| repo.join(&path));
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let updated = release::set_cargo_version(&contents, plan.next)
+            .with_context(|| format!("Failed to update {}", path.display()))?;
+        std::fs::write(&path, updated)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        if args.format == BumpFormat::Text {
+            println!(
+                "\n{BRIGHT_GREEN}📝 Updated {} to {}{RESET}",
+                path.display(),
+                plan.next
+            );
+        }
+    }
+    Ok(())
+}
+
+fn run_range_lint(range: &str, args: &LintArgs, git: &GitCommit) -> Result<()> {
+    let options = RangeLintOptions {
+        include_merges: args.include_merges,
+        include_fixups: args.include_fixups,
+    };
+    let report = CommitLinter::lint_commits(&git.log(range)?, &options)?;
+
+    match args.format {
+        ReportFormat::Json => println!("{}", report.to_json()?),
+        ReportFormat::Junit => print!("{}", report.to_junit()),
+        ReportFormat::Text => {
+            for result in &report.results {
+                let short_sha = &result.sha[..result.sha.len().min(7)];
+                match result.status {
+                    CommitLintStatus::Passed => {
+                        println!("{GREEN}✔{RESET} {DIM}{short_sha}{RESET} {}", result.subject)
+                    }
+                    CommitLintStatus::Skipped => {
+                        println!("{DIM}- {short_sha} {} (skipped){RESET}", result.subject)
+                    }
+                    CommitLintStatus::Failed => {
+                        println!("{RED}✖{RESET} {DIM}{short_sha}{RESET} {}", result.subject);
+                        if let Some(issue) = &result.issue {
+                            println!("    {RED}{issue}{RESET}");
+                        }
+                        if let Some(suggestion) = &result.suggestion {
+                            println!("    {BOLD}{GREEN}suggestion:{RESET} {suggestion}");
+                        }
+                    }
+                }
+            }
+            println!(
+                "\n{BOLD}{} checked, {} failed, {} skipped{RESET}",
+                report.count(CommitLintStatus::Passed) + report.count(CommitLintStatus::Failed),
+                report.count(CommitLintStatus::Failed),
+                report.count(CommitLintStatus::Skipped)
+            );
+        }
+    }
+
+    if !report.is_ok() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn print_lint_report(report: &LintReport) {
+    if let Some(issue) = &report.issue {
+        eprintln!("{RED}❌ {issue}{RESET}");
+        if !report.subject.is_empty() {
+            eprintln!("   {DIM}subject:{RESET} {}", report.subject);
+        }
+        if let Some(suggestion) = &report.suggestion {
+            eprintln!("   {BOLD}{GREEN}suggestion:{RESET} {suggestion}");
+        }
+    }
+}
+
+fn describe_hook_status(status: &HookStatus) -> &'static str {
+    match status {
+        HookStatus::Missing => "not installed",
+        HookStatus::Installed { chained: false } => "installed",
+        HookStatus::Installed { chained: true } => "installed, chaining to the previous hook",
+        HookStatus::Foreign => "another hook is installed",
+    }
+}
+
+/// Take the `-C`/`--repo` options that lead the command line out of it, as git does
+/// Any option before a subcommand turns the subcommand name into a commit message
+/// (`args_conflicts_with_subcommands`), so `amoji -C repo log` must not reach clap as is.
+/// Later `-C` paths are relative to earlier ones, like `git -C a -C b`.
+fn split_leading_repo(mut argv: Vec<OsString>) -> (Option<PathBuf>, Vec<OsString>) {
+    let mut repo: Option<PathBuf> = None;
+    while let Some(arg) = argv.get(1).and_then(|arg| arg.to_str()) {
+        let inline = arg
+            .strip_prefix("--repo=")
+            .or_else(|| arg.strip_prefix("-C"));
+        let (path, taken) = match (arg, inline) {
+            ("-C" | "--repo", _) if argv.len() > 2 => (PathBuf::from(&argv[2]), 2),
+            (_, Some(path)) if !path.is_empty() => (PathBuf::from(path), 1),
+            _ => break,
+        };
+        repo = Some(match repo {
+            Some(repo) => repo.join(path),
+            None => path,
+        });
+        argv.drain(1..=taken);
+    }
+    (repo, argv)
+}
+
 fn main() -> Result<()> {
-    let args = Args::parse();
+    let (leading_repo, argv) = split_leading_repo(std::env::args_os().collect());
+    let mut args = Args::parse_from(argv);
+    if let Some(leading_repo) = leading_repo {
+        args.repo = Some(match args.repo.take() {
+            Some(repo) => leading_repo.join(repo),
+            None => leading_repo,
+        });
+    }
+
+    let backend = match &args.repo {
+        Some(repo) if !repo.is_dir() => {
+            eprintln!(
+                "{RED}❌ Repository path does not exist: {}{RESET}",
+                repo.display()
+            );
+            std::process::exit(GitError::NotInRepository.exit_code());
+        }
+        Some(repo) => ProcessBackend::in_repo(repo),
+        None => ProcessBackend::new(),
+    };
+
+    run(args, &backend)
+}
+
+fn run(args: Args, backend: &dyn GitBackend) -> Result<()> {
+    let git = GitCommit::new(backend);
+
+    match args.command {
+        Some(Command::Hook { hook }) => return run_hook(hook, &git),
+        Some(Command::Lint(lint_args)) => return run_lint(lint_args, &git),
+        Some(Command::Generate(generate_args)) => return run_generate(generate_args, &git),
+        Some(Command::Changelog(changelog_args)) => return run_changelog(changelog_args, &git),
+        Some(Command::Bump(bump_args)) => return run_bump(bump_args, args.repo.as_deref(), &git),
+        Some(Command::Stats(stats_args)) => return run_stats(stats_args, &git),
+        Some(Command::Log(log_args)) => return run_log(log_args, &git),
+        None => {}
+    }
+
+    if args.generate {
+        let generate_args = GenerateArgs {
+            hint: args.message,
+            dry_run: args.dry_run,
+            print: false,
+            diff_budget: args.diff_budget,
+            git_args: args.git_args,
+        };
+        return run_generate(generate_args, &git);
+    }
 
     // Handle help message flag
     if args.help_message {
@@ -109,23 +836,69 @@ fn main() -> Result<()> {
         .message
         .ok_or_else(|| anyhow::anyhow!("Message argument is required"))?;
 
-    // Create matcher and find appropriate emoji
-    let matcher = if cfg!(feature = "llm") {
-        #[cfg(feature = "llm")]
-        {
-            // just SiliconFlow is supported now
-            MatcherFactory::llm_with_fallback(LLMConfig::from_env(
-                LLMProvider::SiliconFlow,
-                LLMModel::Qwen2_7bInstruct,
-            )?)
-        }
-        #[cfg(not(feature = "llm"))]
-        {
-            MatcherFactory::simple()
-        }
+    // Merges, reverts, cherry-picks and autosquash commits don't need a match
+    let state = if git.is_git_repository().unwrap_or(false) {
+        git.repo_state().unwrap_or_default()
     } else {
-        MatcherFactory::simple()
+        RepoState::default()
     };
+    if let Some(special) = SpecialCommit::detect(&message, &state) {
+        if args.explain {
+            println!("{BOLD}{CYAN}🔎 special commit:{RESET} {}", special.reason());
+            println!("  {DIM}the matchers are not consulted{RESET}");
+            print_explained_code(special.code());
+            return Ok(());
+        }
+
+        let formatted_message = match special.code() {
+            Some(code) => {
+                let emoji_unicode = EmojiLookup::code_to_unicode(code).unwrap_or("❓");
+                println!(
+                    "{BOLD}{GREEN}🎯 Matched emoji:{RESET} {emoji_unicode} {DIM}{code}{RESET} ({})",
+                    special.reason()
+                );
+                GitCommit::format_message(code, &message)
+            }
+            None => {
+                println!("{BOLD}{GREEN}🎯 No emoji:{RESET} {}", special.reason());
+                message
+            }
+        };
+        println!("{BOLD}{BLUE}📝 Full message:{RESET} {formatted_message}");
+        commit_and_report(&git, &formatted_message, &args.git_args, args.dry_run);
+        return Ok(());
+    }
+
+    // A declared breaking change always wins
+    let mut signals = DiffAnalyzer::message_signals(&message);
+    for signal in &signals {
+        let unicode = EmojiLookup::code_to_unicode(signal.code).unwrap_or("❓");
+        println!(
+            "{BOLD}{MAGENTA}💬 Message:{RESET} {unicode} {DIM}{}{RESET} ({})",
+            signal.code, signal.reason
+        );
+    }
+
+    // Let the staged changes decide when the message has no recognizable keyword
+    let staged = staged_signals(&git);
+    for signal in &staged {
+        let unicode = EmojiLookup::code_to_unicode(signal.code).unwrap_or("❓");
+        println!(
+            "{BOLD}{MAGENTA}📂 Staged changes:{RESET} {unicode} {DIM}{}{RESET} ({})",
+            signal.code, signal.reason
+        );
+    }
+
+    signals.extend(staged);
+
+    // Create matcher and find appropriate emoji
+    let matcher = MatcherFactory::diff_aware(create_matcher()?, signals);
+    if args.explain {
+        let explanation = matcher.explain(&message)?;
+        print_explanation(&explanation, 0);
+        print_explained_code(explanation.code.as_deref());
+        return Ok(());
+    }
     let match_result = matcher.match_emoji(&message)?;
 
     if let Some((emoji_code, formatted_message)) = match_result {
@@ -136,39 +909,138 @@ fn main() -> Result<()> {
         println!("{BOLD}{GREEN}🎯 Matched emoji:{RESET} {emoji_unicode} {DIM}{emoji_code}{RESET}",);
         println!("{BOLD}{BLUE}📝 Full message:{RESET} {formatted_message}");
 
-        // Check for staged changes before committing
-        if !args.dry_run {
-            match GitCommit::has_staged_changes() {
-                Ok(true) => {
-                    println!("{BRIGHT_GREEN}✅ Staged changes detected{RESET}");
-                }
-                Ok(false) => {
-                    println!(
-                        "{YELLOW}⚠️  No staged changes found. Please stage your changes first with {BOLD}'git add'{RESET}",
-                    );
-                    return Ok(());
-                }
-                Err(e) => {
-                    println!("{YELLOW}⚠️  Could not check Git status: {e}{RESET}");
-                    println!("   {DIM}Proceeding anyway...{RESET}");
-                }
-            }
+        commit_and_report(&git, &formatted_message, &args.git_args, args.dry_run);
+    } else {
+        eprintln!("{RED}❌ Could not find appropriate emoji for message{RESET}",);
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Print a matcher's trace, with the matchers it consulted indented below it
+fn print_explanation(explanation: &Explanation, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let code = explanation.code.as_deref().unwrap_or("nothing");
+    println!(
+        "{indent}{BOLD}{CYAN}🔎 {}:{RESET} {DIM}{code}{RESET}",
+        explanation.matcher
+    );
+    for inner in &explanation.inner {
+        print_explanation(inner, depth + 1);
+    }
+    for step in &explanation.steps {
+        println!("{indent}  {DIM}{step}{RESET}");
+    }
+}
+
+/// The outcome line of `--explain`
+fn print_explained_code(code: Option<&str>) {
+    match code {
+        Some(code) => {
+            let emoji_unicode = EmojiLookup::code_to_unicode(code).unwrap_or("❓");
+            println!("{BOLD}{GREEN}🎯 Matched emoji:{RESET} {emoji_unicode} {DIM}{code}{RESET}");
         }
+        None => println!("{BOLD}{GREEN}🎯 No emoji{RESET}"),
+    }
+}
 
-        // Execute the commit
-        match GitCommit::commit(&formatted_message, args.dry_run) {
-            Ok(result) => {
-                println!("{BRIGHT_GREEN}✅ {RESET} {result}");
+/// Commit unless nothing is staged, exiting with the git error's code on failure
+fn commit_and_report(git: &GitCommit, message: &str, git_args: &[String], dry_run: bool) {
+    // Check for staged changes before committing
+    if !dry_run && !GitCommit::allows_unstaged(git_args) {
+        match git.has_staged_changes() {
+            Ok(true) => {
+                println!("{BRIGHT_GREEN}✅ Staged changes detected{RESET}");
+            }
+            Ok(false) => {
+                println!(
+                    "{YELLOW}⚠️  No staged changes found. Please stage your changes first with {BOLD}'git add'{RESET}",
+                );
+                return;
             }
             Err(e) => {
-                eprintln!("{RED}❌ Commit failed: {RESET} {e}");
-                std::process::exit(1);
+                println!("{YELLOW}⚠️  Could not check Git status: {e}{RESET}");
+                println!("   {DIM}Proceeding anyway...{RESET}");
             }
         }
-    } else {
-        eprintln!("{RED}❌ Could not find appropriate emoji for message{RESET}",);
+    }
+
+    // Execute the commit
+    match git.commit_with_args(message, git_args, dry_run) {
+        Ok(result) => {
+            println!("{BRIGHT_GREEN}✅ {RESET} {result}");
+        }
+        Err(e) => {
+            eprintln!("{RED}❌ Commit failed: {RESET} {e}");
+            let code = e.downcast_ref::<GitError>().map_or(1, GitError::exit_code);
+            std::process::exit(code);
+        }
+    }
+}
+
+fn run_generate(args: GenerateArgs, git: &GitCommit) -> Result<()> {
+    if !git.is_git_repository()? {
+        eprintln!("{RED}❌ {}{RESET}", GitError::NotInRepository);
+        std::process::exit(GitError::NotInRepository.exit_code());
+    }
+
+    let diff = DiffAnalyzer::staged_summary(git, args.diff_budget)?;
+    if diff.files.is_empty() {
+        println!(
+            "{YELLOW}⚠️  No staged changes found. Please stage your changes first with {BOLD}'git add'{RESET}",
+        );
+        return Ok(());
+    }
+
+    let mut signals = args
+        .hint
+        .as_deref()
+        .map(DiffAnalyzer::message_signals)
+        .unwrap_or_default();
+    signals.extend(staged_signals(git));
+
+    // Keep stdout to the message alone when printing for scripts
+    if !args.print {
+        println!(
+            "{BOLD}{MAGENTA}📂 Staged diff:{RESET} {} file{}{}",
+            diff.files.len(),
+            if diff.files.len() == 1 { "" } else { "s" },
+            if diff.omitted_hunks > 0 {
+                format!(
+                    ", {} hunks left out to fit {} bytes",
+                    diff.omitted_hunks, args.diff_budget
+                )
+            } else {
+                String::new()
+            }
+        );
+    }
+
+    let context = GenerationContext {
+        diff,
+        signals: signals.clone(),
+        hint: args.hint,
+    };
+    let matcher = MatcherFactory::diff_aware(create_matcher()?, signals);
+    let Some(generated) = matcher.generate_message(&context)? else {
+        eprintln!("{RED}❌ Could not generate a commit message{RESET}");
         std::process::exit(1);
+    };
+    let message = generated.to_message();
+
+    if args.print {
+        println!("{message}");
+        return Ok(());
     }
 
+    let emoji_unicode = EmojiLookup::code_to_unicode(&generated.code).unwrap_or("❓");
+    println!(
+        "{BOLD}{GREEN}🎯 Matched emoji:{RESET} {emoji_unicode} {DIM}{}{RESET}",
+        generated.code
+    );
+    println!("{BOLD}{BLUE}📝 Full message:{RESET} {message}");
+
+    commit_and_report(git, &message, &args.git_args, args.dry_run);
     Ok(())
 }
@@ -0,0 +1,240 @@
+use super::{
+    Candidate, Explanation, GeneratedMessage, GenerationContext, GitmojiMatcher, MatcherResult,
+    simple::SimpleMatcher,
+};
+use crate::diff::DiffSignal;
+use anyhow::Result;
+
+/// Matcher that weighs signals from the staged changes against the commit message
+/// A message with a known keyword keeps its match unless a signal refines that code;
+/// otherwise the strongest signal replaces the inner matcher's guess
+pub struct DiffAwareMatcher {
+    inner: Box<dyn GitmojiMatcher>,
+    keywords: SimpleMatcher,
+    signals: Vec<DiffSignal>,
+}
+
+impl DiffAwareMatcher {
+    pub fn new(inner: Box<dyn GitmojiMatcher>, signals: Vec<DiffSignal>) -> Self {
+        Self {
+            inner,
+            keywords: SimpleMatcher::new(),
+            signals,
+        }
+    }
+
+    /// The signals this matcher was created with, strongest first
+    pub fn signals(&self) -> &[DiffSignal] {
+        &self.signals
+    }
+
+    /// The strongest signal that should replace `code` matched for `message`
+    fn overriding_signal(&self, message: &str, code: Option<&str>) -> Option<&DiffSignal> {
+        let has_keyword = self.keywords.has_keyword_match(message);
+
+        self.signals.iter().find(|signal| match code {
+            Some(code) if has_keyword => signal.overrides(code),
+            _ => true,
+        })
+    }
+}
+
+impl GitmojiMatcher for DiffAwareMatcher {
+    fn match_emoji(&self, message: &str) -> Result<MatcherResult> {
+        let result = self.inner.match_emoji(message)?;
+        let code = result.as_ref().map(|(code, _)| code.as_str());
+
+        match self.overriding_signal(message, code) {
+            Some(signal) => {
+                let formatted_message = format!("{} {message}", signal.code);
+                Ok(Some((signal.code.to_string(), formatted_message)))
+            }
+            None => Ok(result),
+        }
+    }
+
+    /// The inner matcher's candidates, led by the signal `match_emoji` would pick
+    /// The staged changes are real evidence, so that signal is scored 1.0
+    fn match_candidates(&self, message: &str, k: usize) -> Result<Vec<Candidate>> {
+        let mut candidates = self.inner.match_candidates(message, k)?;
+        let code = candidates.first().map(|candidate| candidate.code.as_str());
+
+        if let Some(signal) = self.overriding_signal(message, code) {
+            candidates.retain(|candidate| candidate.code != signal.code);
+            candidates.insert(
+                0,
+                Candidate {
+                    code: signal.code.to_string(),
+                    score: 1.0,
+                    matcher: self.name(),
+                    reason: signal.reason.clone(),
+                },
+            );
+            candidates.truncate(k);
+        }
+        Ok(candidates)
+    }
+
+    /// The inner matcher's trace, then the signals and whether one overrode its match
+    fn explain(&self, message: &str) -> Result<Explanation> {
+        let inner = self.inner.explain(message)?;
+        let mut explanation = Explanation::new(self.name());
+
+        if self.signals.is_empty() {
+            explanation.step("no signals from the staged changes");
+        }
+        for signal in &self.signals {
+            explanation.step(format!("signal {}: {}", signal.code, signal.reason));
+        }
+
+        explanation.code = match self.overriding_signal(message, inner.code.as_deref()) {
+            Some(signal) => {
+                explanation.step(format!(
+                    "signal {} overrides the message match",
+                    signal.code
+                ));
+                Some(signal.code.to_string())
+            }
+            None => {
+                if !self.signals.is_empty() {
+                    explanation.step("the message match stands");
+                }
+                inner.code.clone()
+            }
+        };
+        explanation.inner.push(inner);
+        Ok(explanation)
+    }
+
+    /// Let the signals weigh in on the gitmoji of a generated message, as for a typed one
+    fn generate_message(&self, context: &GenerationContext) -> Result<Option<GeneratedMessage>> {
+        let Some(mut message) = self.inner.generate_message(context)? else {
+            return Ok(None);
+        };
+
+        if let Some(signal) = self.overriding_signal(&message.subject, Some(&message.code)) {
+            message.code = signal.code.to_string();
+        }
+        Ok(Some(message))
+    }
+
+    fn name(&self) -> &'static str {
+        "diff-aware"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matcher::MatcherFactory;
+
+    fn docs_signal() -> DiffSignal {
+        DiffSignal::new(":memo:", "only documentation files are staged")
+    }
+
+    #[test]
+    fn test_signal_replaces_fallback() {
+        let matcher = DiffAwareMatcher::new(MatcherFactory::simple(), vec![docs_signal()]);
+
+        let (code, message) = matcher.match_emoji("update stuff").unwrap().unwrap();
+        assert_eq!(code, ":memo:");
+        assert_eq!(message, ":memo: update stuff");
+    }
+
+    #[test]
+    fn test_explain_reports_signals() {
+        let matcher = DiffAwareMatcher::new(MatcherFactory::simple(), vec![docs_signal()]);
+
+        let explanation = matcher.explain("update stuff").unwrap();
+        assert_eq!(explanation.code.as_deref(), Some(":memo:"));
+        assert_eq!(
+            explanation.steps,
+            [
+                "signal :memo:: only documentation files are staged",
+                "signal :memo: overrides the message match",
+            ]
+        );
+        assert_eq!(explanation.inner[0].matcher, "simple");
+        assert_eq!(explanation.inner[0].code.as_deref(), Some(":sparkles:"));
+
+        let explanation = matcher.explain("fix broken link").unwrap();
+        assert_eq!(explanation.code.as_deref(), Some(":bug:"));
+        assert_eq!(
+            explanation.steps.last().unwrap(),
+            "the message match stands"
+        );
+    }
+
+    #[test]
+    fn test_keyword_wins_over_signal() {
+        let matcher = DiffAwareMatcher::new(MatcherFactory::simple(), vec![docs_signal()]);
+
+        let (code, _) = matcher.match_emoji("fix broken link").unwrap().unwrap();
+        assert_eq!(code, ":bug:");
+    }
+
+    #[test]
+    fn test_signal_refines_generic_keyword() {
+        let signal = DiffSignal::new(":heavy_plus_sign:", "added serde").refining(&[":package:"]);
+        let matcher = DiffAwareMatcher::new(MatcherFactory::simple(), vec![signal]);
+
+        let (code, _) = matcher.match_emoji("update deps").unwrap().unwrap();
+        assert_eq!(code, ":heavy_plus_sign:");
+
+        // Keywords the signal doesn't refine are kept
+        let (code, _) = matcher.match_emoji("fix build").unwrap().unwrap();
+        assert_eq!(code, ":bug:");
+    }
+
+    #[test]
+    fn test_decisive_signal_overrides_keyword() {
+        let signal = DiffSignal::new(":boom:", "conventional commit `!` marker").decisive();
+        let matcher = DiffAwareMatcher::new(MatcherFactory::simple(), vec![signal]);
+
+        let (code, message) = matcher.match_emoji("feat!: drop v1 API").unwrap().unwrap();
+        assert_eq!(code, ":boom:");
+        assert_eq!(message, ":boom: feat!: drop v1 API");
+    }
+
+    #[test]
+    fn test_generate_message_applies_signals() {
+        let signals = vec![docs_signal()];
+        let matcher = DiffAwareMatcher::new(MatcherFactory::simple(), signals.clone());
+        let context = GenerationContext {
+            hint: Some("reword the install guide".to_string()),
+            signals,
+            ..Default::default()
+        };
+
+        let message = matcher.generate_message(&context).unwrap().unwrap();
+        assert_eq!(message.to_message(), ":memo: reword the install guide");
+    }
+
+    #[test]
+    fn test_match_candidates_puts_signal_first() {
+        let matcher = DiffAwareMatcher::new(MatcherFactory::simple(), vec![docs_signal()]);
+
+        let candidates = matcher.match_candidates("update stuff", 3).unwrap();
+        let codes: Vec<&str> = candidates.iter().map(|c| c.code.as_str()).collect();
+        assert_eq!(codes, [":memo:", ":sparkles:"]);
+        assert_eq!(candidates[0].score, 1.0);
+        assert_eq!(candidates[0].matcher, "diff-aware");
+        assert_eq!(candidates[0].reason, "only documentation files are staged");
+        assert_eq!(candidates[1].score, 0.0);
+
+        // A keyword the signal doesn't refine keeps its place
+        let candidates = matcher.match_candidates("fix broken link", 3).unwrap();
+        assert_eq!(candidates[0].code, ":bug:");
+        assert_eq!(candidates[0].matcher, "simple");
+    }
+
+    #[test]
+    fn test_without_signals_uses_inner() {
+        let matcher = DiffAwareMatcher::new(MatcherFactory::simple(), vec![]);
+
+        assert_eq!(matcher.name(), "diff-aware");
+        assert!(matcher.signals().is_empty());
+        let (code, _) = matcher.match_emoji("update stuff").unwrap().unwrap();
+        assert_eq!(code, ":sparkles:");
+    }
+}
@@ -1,6 +1,8 @@
 #[cfg(feature = "llm")]
 use {
-    super::{GitmojiMatcher, MatcherResult},
+    super::{
+        Candidate, Explanation, GeneratedMessage, GenerationContext, GitmojiMatcher, MatcherResult,
+    },
     crate::emoji::EmojiLookup,
     anyhow::{Result, anyhow},
     regex::Regex,
@@ -268,6 +270,69 @@ User Commit Message:
         )
     }
 
+    /// Generate the LLM prompt for ranking up to `k` gitmojis
+    fn create_candidates_prompt(&self, commit_message: &str, k: usize) -> String {
+        format!(
+            r#"You are a git commit message expert. Your task is to rank the gitmojis that fit a commit message.
+
+Available gitmojis:
+{gitmoji_data}
+
+Instructions:
+1. Analyze the commit message to understand what type of change it represents
+2. Select up to {k} gitmojis from the list above, the most appropriate first
+3. Respond ONLY with one line per gitmoji in this exact format: ":emoji_code: short reason"
+
+Example:
+- Input: "fix typo in login error message"
+  Output:
+:pencil2: fixes a typo
+:bug: fixes the login flow
+
+User Commit Message:
+{commit_message}"#,
+            gitmoji_data = self.gitmoji_data
+        )
+    }
+
+    /// Generate the LLM prompt for writing a whole message from the staged diff
+    /// Same gitmoji list and subject format as `create_prompt`, with the diff as context
+    fn create_generation_prompt(&self, context: &GenerationContext) -> String {
+        let mut extra = String::new();
+        if !context.signals.is_empty() {
+            extra.push_str("\nHints from analyzing the changes:\n");
+            for signal in &context.signals {
+                extra.push_str(&format!("- {} ({})\n", signal.code, signal.reason));
+            }
+        }
+        if let Some(hint) = &context.hint {
+            extra.push_str(&format!("\nThe author describes the change as:\n{hint}\n"));
+        }
+
+        format!(
+            r#"You are a git commit message expert. Your task is to write a commit message for the staged changes below.
+
+Available gitmojis:
+{gitmoji_data}
+
+Instructions:
+1. Analyze the diff to understand what type of change it represents
+2. Select the most appropriate gitmoji from the list above
+3. Write a short imperative subject line, under 72 characters
+4. Add a body after a blank line only if the change needs explaining
+5. Respond ONLY with the commit message, the first line in this exact format: ":emoji_code: subject"
+
+Examples:
+- Output: ":bug: fix login redirect loop"
+- Output: ":sparkles: add user authentication\n\nSessions are stored in signed cookies."
+{extra}
+Staged changes (file list with line counts, then hunks; some may be omitted):
+{diff}"#,
+            gitmoji_data = self.gitmoji_data,
+            diff = context.diff.text
+        )
+    }
+
     /// Call the LLM API
     async fn call_llm_api(&self, prompt: &str) -> Result<String> {
         self.config
@@ -283,20 +348,112 @@ User Commit Message:
 
     /// Parse LLM response to extract gitmoji and message
     fn parse_llm_response(&self, response: &str) -> Option<(String, String)> {
+        // Validate that the emoji code exists in our lookup
+        self.parse_reply_format(response)
+            .filter(|(emoji_code, _)| EmojiLookup::code_to_unicode(emoji_code).is_some())
+    }
+
+    /// Split a ":emoji_code: message" reply, whether or not the code is a known gitmoji
+    fn parse_reply_format(&self, response: &str) -> Option<(String, String)> {
         // Pattern to match ":emoji_code: message" format
         let re = Regex::new(r"^:([a-zA-Z0-9_]+):\s*(.+)$").ok()?;
 
-        if let Some(captures) = re.captures(response.trim()) {
-            let emoji_code = format!(":{}:", captures.get(1)?.as_str());
-            let message = captures.get(2)?.as_str().to_string();
+        let captures = re.captures(response.trim())?;
+        let emoji_code = format!(":{}:", captures.get(1)?.as_str());
+        let message = captures.get(2)?.as_str().to_string();
+        Some((emoji_code, message))
+    }
+
+    /// Parse ranked ":emoji_code: reason" lines, skipping unknown and repeated codes
+    /// Scores follow the rank, as the model gives no confidence of its own
+    fn parse_candidates_response(&self, response: &str, k: usize) -> Vec<Candidate> {
+        let mut weights: Vec<(String, f64, String)> = Vec::new();
+        for line in response.lines() {
+            // Models like to number or bullet their lists
+            let line = line
+                .trim()
+                .trim_start_matches(|c: char| {
+                    c.is_ascii_digit() || matches!(c, '.' | ')' | '-' | '*')
+                })
+                .trim();
+            let Some((code, reason)) = self.parse_llm_response(line) else {
+                continue;
+            };
+            if weights.iter().any(|(known, ..)| *known == code) {
+                continue;
+            }
+            let weight = Candidate::rank_weight(weights.len());
+            weights.push((code, weight, reason));
+        }
+        Candidate::from_weights(weights, self.name(), k)
+    }
+
+    /// The reply inside a code fence, whose opening line may name a language (```text, ```markdown)
+    fn strip_code_fence(response: &str) -> &str {
+        let response = response.trim();
+        let Some(fenced) = response.strip_prefix("```") else {
+            return response;
+        };
+        let inner = fenced
+            .split_once('\n')
+            .map_or(fenced, |(_language, rest)| rest);
+        inner.trim_end().strip_suffix("```").unwrap_or(inner).trim()
+    }
+
+    /// Parse a generated message: a ":emoji_code: subject" line, then an optional body
+    fn parse_generated_response(&self, response: &str) -> Option<GeneratedMessage> {
+        let response = Self::strip_code_fence(response);
+        let (subject_line, body) = response.split_once('\n').unwrap_or((response, ""));
+
+        // Or the subject alone in inline code
+        let (code, subject) = self.parse_llm_response(subject_line.trim().trim_matches('`'))?;
+        let body = body.trim();
+        Some(GeneratedMessage {
+            code,
+            subject,
+            body: (!body.is_empty()).then(|| body.to_string()),
+        })
+    }
+
+    /// Generate a whole message with async LLM call
+    pub async fn generate_message_async(
+        &self,
+        context: &GenerationContext,
+    ) -> Result<Option<GeneratedMessage>> {
+        if context.diff.files.is_empty() {
+            return Ok(None);
+        }
+
+        let prompt = self.create_generation_prompt(context);
 
-            // Validate that the emoji code exists in our lookup
-            if EmojiLookup::code_to_unicode(&emoji_code).is_some() {
-                return Some((emoji_code, message));
+        match self.call_llm_api(&prompt).await {
+            // If parsing fails, return None to trigger fallback
+            Ok(response) => Ok(self.parse_generated_response(&response)),
+            Err(e) => {
+                // If LLM call fails, return None to trigger fallback
+                eprintln!("LLM call failed: {e}");
+                Ok(None)
             }
         }
+    }
+
+    /// Rank gitmojis with async LLM call
+    pub async fn match_candidates_async(&self, message: &str, k: usize) -> Result<Vec<Candidate>> {
+        if message.trim().is_empty() || k == 0 {
+            return Ok(Vec::new());
+        }
+
+        let prompt = self.create_candidates_prompt(message, k);
 
-        None
+        match self.call_llm_api(&prompt).await {
+            // If parsing fails, return no candidates to trigger fallback
+            Ok(response) => Ok(self.parse_candidates_response(&response, k)),
+            Err(e) => {
+                // If LLM call fails, return no candidates to trigger fallback
+                eprintln!("LLM call failed: {e}");
+                Ok(Vec::new())
+            }
+        }
     }
 
     /// Match emoji with async LLM call
@@ -321,11 +478,53 @@ User Commit Message:
             }
             Err(e) => {
                 // If LLM call fails, return None to trigger fallback
-                println!("LLM call failed: {e}");
+                eprintln!("LLM call failed: {e}");
                 Ok(None)
             }
         }
     }
+
+    /// Trace `match_emoji_async`: the prompt size, the raw reply and how it parsed
+    pub async fn explain_async(&self, message: &str) -> Result<Explanation> {
+        let mut explanation = Explanation::new(self.name());
+        if message.trim().is_empty() {
+            explanation.step("empty message, the model is not asked");
+            return Ok(explanation);
+        }
+
+        let prompt = self.create_prompt(message);
+        explanation.step(format!(
+            "prompt: {} chars, model {}",
+            prompt.chars().count(),
+            self.config.model.model_id()
+        ));
+
+        match self.call_llm_api(&prompt).await {
+            Ok(response) => self.explain_reply(&mut explanation, &response),
+            Err(e) => explanation.step(format!("LLM call failed: {e}")),
+        }
+        Ok(explanation)
+    }
+
+    /// Record the raw reply and whether `match_emoji_async` would accept it
+    fn explain_reply(&self, explanation: &mut Explanation, response: &str) {
+        explanation.step(format!("reply: {response:?}"));
+
+        match self.parse_reply_format(response) {
+            Some((emoji_code, improved_message))
+                if EmojiLookup::code_to_unicode(&emoji_code).is_some() =>
+            {
+                explanation.step(format!(
+                    "parsed {emoji_code} with message {improved_message:?}"
+                ));
+                explanation.code = Some(emoji_code);
+            }
+            Some((emoji_code, _)) => {
+                explanation.step(format!("parsed {emoji_code}, which is not a known gitmoji"));
+            }
+            None => explanation.step("the reply is not in the \":emoji_code: message\" format"),
+        }
+    }
 }
 
 #[cfg(feature = "llm")]
@@ -337,6 +536,21 @@ impl GitmojiMatcher for LLMMatcher {
         rt.block_on(self.match_emoji_async(message))
     }
 
+    fn match_candidates(&self, message: &str, k: usize) -> Result<Vec<Candidate>> {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(self.match_candidates_async(message, k))
+    }
+
+    fn explain(&self, message: &str) -> Result<Explanation> {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(self.explain_async(message))
+    }
+
+    fn generate_message(&self, context: &GenerationContext) -> Result<Option<GeneratedMessage>> {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(self.generate_message_async(context))
+    }
+
     fn name(&self) -> &'static str {
         "llm"
     }
@@ -366,11 +580,58 @@ impl LLMWithFallbackMatcher {
             Some(result) => Ok(Some(result)),
             None => {
                 // Fallback to simple matcher
-                println!("LLM matcher failed, falling back to simple matcher");
+                eprintln!("LLM matcher failed, falling back to simple matcher");
                 self.fallback_matcher.match_emoji(message)
             }
         }
     }
+
+    /// Try LLM first, then rank with the simple matcher
+    pub async fn match_candidates_async(&self, message: &str, k: usize) -> Result<Vec<Candidate>> {
+        let candidates = self.llm_matcher.match_candidates_async(message, k).await?;
+        if !candidates.is_empty() {
+            return Ok(candidates);
+        }
+
+        eprintln!("LLM matcher failed, falling back to simple matcher");
+        self.fallback_matcher.match_candidates(message, k)
+    }
+
+    /// Trace the LLM matcher, then the simple matcher if the LLM gave no usable answer
+    pub async fn explain_async(&self, message: &str) -> Result<Explanation> {
+        let mut explanation = Explanation::new("llm_with_fallback");
+        let llm = self.llm_matcher.explain_async(message).await?;
+
+        if let Some(code) = &llm.code {
+            explanation.step("the LLM answer is used");
+            explanation.code = Some(code.clone());
+            explanation.inner.push(llm);
+            return Ok(explanation);
+        }
+
+        let reason = llm.steps.last().cloned().unwrap_or_default();
+        explanation.step(format!("falling back to the simple matcher: {reason}"));
+        explanation.inner.push(llm);
+
+        let fallback = self.fallback_matcher.explain(message)?;
+        explanation.code = fallback.code.clone();
+        explanation.inner.push(fallback);
+        Ok(explanation)
+    }
+
+    /// Try LLM first, then draft the message with the simple matcher
+    pub async fn generate_message_async(
+        &self,
+        context: &GenerationContext,
+    ) -> Result<Option<GeneratedMessage>> {
+        match self.llm_matcher.generate_message_async(context).await? {
+            Some(message) => Ok(Some(message)),
+            None => {
+                eprintln!("LLM matcher failed, falling back to simple matcher");
+                self.fallback_matcher.generate_message(context)
+            }
+        }
+    }
 }
 
 #[cfg(feature = "llm")]
@@ -380,6 +641,21 @@ impl GitmojiMatcher for LLMWithFallbackMatcher {
         rt.block_on(self.match_emoji_async(message))
     }
 
+    fn match_candidates(&self, message: &str, k: usize) -> Result<Vec<Candidate>> {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(self.match_candidates_async(message, k))
+    }
+
+    fn explain(&self, message: &str) -> Result<Explanation> {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(self.explain_async(message))
+    }
+
+    fn generate_message(&self, context: &GenerationContext) -> Result<Option<GeneratedMessage>> {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(self.generate_message_async(context))
+    }
+
     fn name(&self) -> &'static str {
         "llm_with_fallback"
     }
@@ -500,6 +776,149 @@ mod tests {
         assert!(prompt.contains("emoji_code"));
     }
 
+    #[test]
+    fn test_generated_response_parsing() {
+        let config = LLMConfig::new(
+            LLMProvider::SiliconFlow,
+            "test-key".to_string(),
+            LLMModel::Qwen2_7bInstruct,
+        )
+        .unwrap();
+
+        let matcher = LLMMatcher::new(config);
+
+        let message = matcher
+            .parse_generated_response(
+                ":bug: fix login loop\n\nThe redirect kept the old session.\n",
+            )
+            .unwrap();
+        assert_eq!(message.code, ":bug:");
+        assert_eq!(message.subject, "fix login loop");
+        assert_eq!(
+            message.body.as_deref(),
+            Some("The redirect kept the old session.")
+        );
+
+        for fenced in [
+            "```\n:memo: update install guide\n```",
+            "```text\n:memo: update install guide\n```",
+            "```markdown\n:memo: update install guide\n```\n",
+            "```\n:memo: update install guide",
+            "`:memo: update install guide`",
+        ] {
+            let message = matcher.parse_generated_response(fenced).unwrap();
+            assert_eq!(message.to_message(), ":memo: update install guide");
+        }
+
+        let message = matcher
+            .parse_generated_response("```md\n:bug: fix login loop\n\nKeep `session` fresh.\n```")
+            .unwrap();
+        assert_eq!(message.subject, "fix login loop");
+        assert_eq!(message.body.as_deref(), Some("Keep `session` fresh."));
+
+        assert!(
+            matcher
+                .parse_generated_response("Here is your message")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_explain_reply() {
+        let config = LLMConfig::new(
+            LLMProvider::SiliconFlow,
+            "test-key".to_string(),
+            LLMModel::Qwen2_7bInstruct,
+        )
+        .unwrap();
+        let matcher = LLMMatcher::new(config);
+
+        let mut explanation = Explanation::new("llm");
+        matcher.explain_reply(&mut explanation, ":bug: fix login crash");
+        assert_eq!(explanation.code.as_deref(), Some(":bug:"));
+        assert_eq!(
+            explanation.steps,
+            [
+                "reply: \":bug: fix login crash\"",
+                "parsed :bug: with message \"fix login crash\"",
+            ]
+        );
+
+        let mut explanation = Explanation::new("llm");
+        matcher.explain_reply(&mut explanation, "I think this is a bug fix");
+        assert_eq!(explanation.code, None);
+        assert_eq!(
+            explanation.steps.last().unwrap(),
+            "the reply is not in the \":emoji_code: message\" format"
+        );
+
+        let mut explanation = Explanation::new("llm");
+        matcher.explain_reply(&mut explanation, ":bugfix: fix login crash");
+        assert_eq!(explanation.code, None);
+        assert_eq!(
+            explanation.steps.last().unwrap(),
+            "parsed :bugfix:, which is not a known gitmoji"
+        );
+    }
+
+    #[test]
+    fn test_candidates_response_parsing() {
+        let config = LLMConfig::new(
+            LLMProvider::SiliconFlow,
+            "test-key".to_string(),
+            LLMModel::Qwen2_7bInstruct,
+        )
+        .unwrap();
+
+        let matcher = LLMMatcher::new(config);
+        let candidates = matcher.parse_candidates_response(
+            "1. :pencil2: fixes a typo\n- :bug: fixes the login flow\n:nonexistent: nope\n:pencil2: again\n:memo: touches docs\n",
+            2,
+        );
+
+        let codes: Vec<&str> = candidates.iter().map(|c| c.code.as_str()).collect();
+        assert_eq!(codes, [":pencil2:", ":bug:"]);
+        assert_eq!(candidates[0].reason, "fixes a typo");
+        assert_eq!(candidates[0].matcher, "llm");
+        assert!(candidates[0].score > candidates[1].score);
+
+        assert!(matcher.parse_candidates_response("no idea", 3).is_empty());
+        assert!(
+            matcher
+                .create_candidates_prompt("fix typo", 3)
+                .contains("up to 3 gitmojis")
+        );
+    }
+
+    #[test]
+    fn test_generation_prompt_creation() {
+        let config = LLMConfig::new(
+            LLMProvider::SiliconFlow,
+            "test-key".to_string(),
+            LLMModel::Qwen2_7bInstruct,
+        )
+        .unwrap();
+
+        let matcher = LLMMatcher::new(config);
+        let context = GenerationContext {
+            diff: crate::diff::summary::DiffSummary {
+                text: "src/lib.rs (+1 -0)\n@@ -1 +1,2 @@\n+fn parse() {}\n".to_string(),
+                ..Default::default()
+            },
+            signals: vec![crate::diff::DiffSignal::new(
+                ":memo:",
+                "only documentation files are staged",
+            )],
+            hint: Some("parser groundwork".to_string()),
+        };
+        let prompt = matcher.create_generation_prompt(&context);
+
+        assert!(prompt.contains(":sparkles:"));
+        assert!(prompt.contains("+fn parse() {}"));
+        assert!(prompt.contains("- :memo: (only documentation files are staged)"));
+        assert!(prompt.contains("parser groundwork"));
+    }
+
     #[test]
     fn test_fallback_matcher_creation() {
         let config = LLMConfig::new(
@@ -1,18 +1,141 @@
+pub mod diff;
 #[cfg(feature = "llm")]
 pub mod llm;
 pub mod simple;
 
+use crate::diff::{DiffSignal, summary::DiffSummary};
 use anyhow::Result;
 
 /// Return type for emoji matches
 pub type MatcherResult = Option<(String, String)>; // (emoji_code, format_message)
 
+/// A possible gitmoji for a message, as ranked by `GitmojiMatcher::match_candidates`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub code: String,
+    /// Confidence from 0.0, a blind default, to 1.0
+    pub score: f64,
+    /// Name of the matcher that proposed the candidate
+    pub matcher: &'static str,
+    /// Why the candidate was proposed, e.g. "keyword 'fix'"
+    pub reason: String,
+}
+
+impl Candidate {
+    /// Weight of the evidence at `rank` (0 for the first), half that of the rank before
+    pub(crate) fn rank_weight(rank: usize) -> f64 {
+        0.5f64.powi(rank.min(64) as i32)
+    }
+
+    /// Candidates from `(code, weight, reason)`, scored by their share of the total weight
+    /// Sorted by score with ties kept in order, and cut to the top `k`
+    pub(crate) fn from_weights(
+        weights: Vec<(String, f64, String)>,
+        matcher: &'static str,
+        k: usize,
+    ) -> Vec<Self> {
+        let total: f64 = weights.iter().map(|(_, weight, _)| weight).sum();
+        let mut candidates: Vec<Self> = weights
+            .into_iter()
+            .map(|(code, weight, reason)| Candidate {
+                code,
+                score: if total > 0.0 { weight / total } else { 0.0 },
+                matcher,
+                reason,
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+        candidates.truncate(k);
+        candidates
+    }
+}
+
+/// How a matcher arrived at its gitmoji, as shown by `amoji --explain`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    pub matcher: &'static str,
+    /// What the matcher looked at and decided, in order
+    pub steps: Vec<String>,
+    /// Explanations of the matchers this one consulted, in order
+    pub inner: Vec<Explanation>,
+    /// The chosen gitmoji, None when the matcher found nothing
+    pub code: Option<String>,
+}
+
+impl Explanation {
+    pub fn new(matcher: &'static str) -> Self {
+        Self {
+            matcher,
+            steps: Vec::new(),
+            inner: Vec::new(),
+            code: None,
+        }
+    }
+
+    /// Record a step of the decision
+    pub fn step(&mut self, step: impl Into<String>) {
+        self.steps.push(step.into());
+    }
+}
+
+/// What a matcher gets to write a whole commit message
+#[derive(Debug, Clone, Default)]
+pub struct GenerationContext {
+    /// The staged diff, cut down to the size budget
+    pub diff: DiffSummary,
+    /// Signals from the message and the staged changes, strongest first
+    pub signals: Vec<DiffSignal>,
+    /// What the user said about the change, if anything
+    pub hint: Option<String>,
+}
+
+/// A whole commit message: gitmoji, subject and optional body
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedMessage {
+    pub code: String,
+    /// The subject without its gitmoji
+    pub subject: String,
+    pub body: Option<String>,
+}
+
+impl GeneratedMessage {
+    /// The subject line with its gitmoji
+    pub fn subject_line(&self) -> String {
+        format!("{} {}", self.code, self.subject)
+    }
+
+    /// The full message as passed to `git commit`
+    pub fn to_message(&self) -> String {
+        match &self.body {
+            Some(body) => format!("{}\n\n{body}", self.subject_line()),
+            None => self.subject_line(),
+        }
+    }
+}
+
 /// Core trait for gitmoji matching strategies
 pub trait GitmojiMatcher {
     /// Match a commit message to an appropriate gitmoji
     /// Returns (emoji_code, formatted_message) or None
     fn match_emoji(&self, message: &str) -> Result<MatcherResult>;
 
+    /// Rank up to `k` gitmojis for a commit message, best first
+    /// Unlike `match_emoji`, a blind default is told apart by its score of 0.0
+    fn match_candidates(&self, message: &str, k: usize) -> Result<Vec<Candidate>>;
+
+    /// Trace how `match_emoji` decides on a gitmoji for the message
+    fn explain(&self, message: &str) -> Result<Explanation> {
+        let mut explanation = Explanation::new(self.name());
+        explanation.code = self.match_emoji(message)?.map(|(code, _)| code);
+        Ok(explanation)
+    }
+
+    /// Write a whole commit message for the staged changes
+    /// Returns None when this matcher can't write one
+    fn generate_message(&self, _context: &GenerationContext) -> Result<Option<GeneratedMessage>> {
+        Ok(None)
+    }
+
     /// Get the name of this matcher
     fn name(&self) -> &'static str;
 }
@@ -26,6 +149,14 @@ impl MatcherFactory {
         Box::new(simple::SimpleMatcher::new())
     }
 
+    /// Wrap a matcher so signals from the staged changes can override a fallback match
+    pub fn diff_aware(
+        inner: Box<dyn GitmojiMatcher>,
+        signals: Vec<DiffSignal>,
+    ) -> Box<dyn GitmojiMatcher> {
+        Box::new(diff::DiffAwareMatcher::new(inner, signals))
+    }
+
     /// Create an LLM matcher with the given configuration
     #[cfg(feature = "llm")]
     pub fn llm(config: llm::LLMConfig) -> Box<dyn GitmojiMatcher> {
@@ -192,6 +323,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_generated_message_format() {
+        let mut message = GeneratedMessage {
+            code: ":bug:".to_string(),
+            subject: "fix parser crash on empty input".to_string(),
+            body: None,
+        };
+        assert_eq!(
+            message.to_message(),
+            ":bug: fix parser crash on empty input"
+        );
+
+        message.body = Some("The lexer returned an empty token list.".to_string());
+        assert_eq!(
+            message.to_message(),
+            ":bug: fix parser crash on empty input\n\nThe lexer returned an empty token list."
+        );
+    }
+
     #[test]
     fn test_matcher_trait_object() {
         // Test that we can use the matcher as a trait object
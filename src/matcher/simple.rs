@@ -1,11 +1,236 @@
-use super::{GitmojiMatcher, MatcherResult};
+use super::{
+    Candidate, Explanation, GeneratedMessage, GenerationContext, GitmojiMatcher, MatcherResult,
+};
+use crate::diff::summary::DiffSummary;
+use aho_corasick::{AhoCorasick, MatchKind};
 use anyhow::Result;
+use rust_stemmers::{Algorithm, Stemmer};
 use serde_json;
-use std::collections::HashMap;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    sync::LazyLock,
+};
+
+/// Gitmoji used when no keyword matches
+const FALLBACK_CODE: &str = ":sparkles:";
+
+/// Shortest word a typo other than two swapped letters is forgiven in
+const MIN_FUZZY_LEN: usize = 4;
+
+/// Shortest word two swapped letters are forgiven in, as in "fxi" for "fix"
+const MIN_SWAP_LEN: usize = 3;
+
+/// Weight of a keyword matched despite a typo, relative to an exact one
+const FUZZY_WEIGHT: f64 = 0.5;
+
+/// First character of the private use area the keyword symbols are taken from
+/// Symbol 0 stands for every token that is not part of any keyword
+const FIRST_SYMBOL: u32 = 0xF0000;
+
+/// Common words one typo away from a keyword, e.g. "text" from "test" or "login" from
+/// "logic"; they are never taken for a misspelled keyword
+static COMMON_WORDS: LazyLock<HashSet<String>> = LazyLock::new(|| {
+    serde_json::from_str(include_str!("../../fixtures/common_words.json"))
+        .expect("common_words.json is a list of words")
+});
+
+/// Finds the keywords of a token stream, single words and phrases alike
+/// Words are compared by their English stems, so "fixes" and "fixing" match "fix",
+/// except where keys with one stem map to different gitmojis, e.g. "experience" and
+/// "experiment": a word with such a stem goes to the key it shares the longest prefix with,
+/// so "experimenting" is "experiment" and "experiences" is "experience".
+/// Each stem or exact word used in a keyword gets its own symbol, so keywords and messages
+/// become strings of symbols, and one Aho-Corasick pass finds every keyword, leftmost-longest
+struct KeywordAutomaton {
+    automaton: AhoCorasick,
+    stemmer: Stemmer,
+    /// Symbol of each stem used in a keyword
+    symbols: HashMap<String, char>,
+    /// Symbol of each keyword word whose stem is ambiguous, matched without stemming
+    exact: HashMap<String, char>,
+    /// The keyword words sharing each ambiguous stem
+    ambiguous: HashMap<String, Vec<String>>,
+    /// The keyword map key behind each pattern of the automaton
+    keys: Vec<String>,
+    /// Letters of the single-word keywords with their keys, for typo-tolerant matching
+    words: Vec<(Vec<char>, String)>,
+}
+
+impl KeywordAutomaton {
+    fn new(keyword_map: &HashMap<String, String>) -> Self {
+        let stemmer = Stemmer::create(Algorithm::English);
+        let mut keys: Vec<&String> = keyword_map.keys().collect();
+        keys.sort();
+
+        // Stems shared by single-word keys with different gitmojis
+        let mut codes_by_stem: HashMap<String, &str> = HashMap::new();
+        let mut ambiguous: HashMap<String, Vec<String>> = HashMap::new();
+        for key in &keys {
+            if let [word] = SimpleMatcher::tokens(key).as_slice() {
+                let stem = stemmer.stem(word).into_owned();
+                let code = keyword_map[*key].as_str();
+                if *codes_by_stem.entry(stem.clone()).or_insert(code) != code {
+                    ambiguous.insert(stem, Vec::new());
+                }
+            }
+        }
+
+        let mut symbols: HashMap<String, char> = HashMap::new();
+        let mut exact: HashMap<String, char> = HashMap::new();
+        let mut patterns: HashMap<String, String> = HashMap::new();
+        let mut words = Vec::new();
+        for key in keys {
+            let tokens = SimpleMatcher::tokens(key);
+            if let [word] = tokens.as_slice() {
+                words.push((word.chars().collect(), key.clone()));
+            }
+
+            let mut pattern = String::new();
+            for word in tokens {
+                let id = (symbols.len() + exact.len()) as u32 + 1;
+                let stem = stemmer.stem(&word).into_owned();
+                let entry = if let Some(words) = ambiguous.get_mut(&stem) {
+                    if !words.contains(&word) {
+                        words.push(word.clone());
+                    }
+                    exact.entry(word)
+                } else {
+                    symbols.entry(stem)
+                };
+                pattern.push(*entry.or_insert_with(|| Self::symbol(id)));
+            }
+            // "hot-fix" and "hot fixes" are the same phrase; the first key is kept
+            if !pattern.is_empty() {
+                patterns.entry(pattern).or_insert_with(|| key.clone());
+            }
+        }
+
+        let (patterns, keys): (Vec<String>, Vec<String>) = patterns.into_iter().unzip();
+        let automaton = AhoCorasick::builder()
+            .match_kind(MatchKind::LeftmostLongest)
+            .build(&patterns)
+            .expect("keyword patterns are small");
+
+        Self {
+            automaton,
+            stemmer,
+            symbols,
+            exact,
+            ambiguous,
+            keys,
+            words,
+        }
+    }
+
+    /// A private use character, 4 bytes long in UTF-8 like every other symbol
+    fn symbol(id: u32) -> char {
+        char::from_u32(FIRST_SYMBOL + id).expect("fewer keyword words than private use characters")
+    }
+
+    /// The stem of a lowercased word, e.g. "refactor" for "refactors"
+    fn stem<'a>(&self, word: &'a str) -> Cow<'a, str> {
+        self.stemmer.stem(word)
+    }
+
+    /// The symbol of a lowercased word, None when it is part of no keyword
+    /// The word itself is looked up first, then its stem
+    fn symbol_of(&self, word: &str) -> Option<char> {
+        if let Some(&symbol) = self.exact.get(word) {
+            return Some(symbol);
+        }
+
+        let stem = self.stem(word);
+        match self.ambiguous.get(stem.as_ref()) {
+            Some(candidates) => {
+                let shared_prefix = |candidate: &&String| {
+                    candidate
+                        .chars()
+                        .zip(word.chars())
+                        .take_while(|(a, b)| a == b)
+                        .count()
+                };
+                let closest = candidates.iter().max_by_key(shared_prefix)?;
+                self.exact.get(closest).copied()
+            }
+            None => self.symbols.get(stem.as_ref()).copied(),
+        }
+    }
+
+    /// The keywords found in the tokens, in order, as the matched words and the keyword map key
+    /// Where keywords overlap, the one starting first wins, then the longest one
+    fn find(&self, tokens: &[String]) -> Vec<(String, &str)> {
+        let text: String = tokens
+            .iter()
+            .map(|token| self.symbol_of(token).unwrap_or(Self::symbol(0)))
+            .collect();
+
+        self.automaton
+            .find_iter(&text)
+            .map(|found| {
+                let words = tokens[found.start() / 4..found.end() / 4].join(" ");
+                (words, self.keys[found.pattern().as_usize()].as_str())
+            })
+            .collect()
+    }
+
+    /// Single-word keywords one typo away from the tokens that are part of no keyword and
+    /// no common word (see `COMMON_WORDS`), in order, as the typed token and the keyword map key
+    fn find_fuzzy(&self, tokens: &[String]) -> Vec<(String, &str)> {
+        tokens
+            .iter()
+            .filter(|token| self.symbol_of(token).is_none() && !COMMON_WORDS.contains(*token))
+            .filter_map(|token| {
+                let typed: Vec<char> = token.chars().collect();
+                let (_, key) = self
+                    .words
+                    .iter()
+                    .find(|(word, _)| Self::one_typo_apart(&typed, word))?;
+                Some((token.clone(), key.as_str()))
+            })
+            .collect()
+    }
+
+    /// Whether `typed` is `word` with one typo (Damerau-Levenshtein distance 1): two
+    /// neighbouring letters swapped, in words of `MIN_SWAP_LEN` letters or more, or one
+    /// letter replaced, added or left out, in words of `MIN_FUZZY_LEN` letters or more
+    fn one_typo_apart(typed: &[char], word: &[char]) -> bool {
+        if typed.len().abs_diff(word.len()) > 1
+            || typed.len().min(word.len()) < MIN_SWAP_LEN
+            || typed == word
+        {
+            return false;
+        }
+
+        let common = typed.iter().zip(word).take_while(|(a, b)| a == b).count();
+        let (typed_rest, word_rest) = (&typed[common..], &word[common..]);
+
+        let swapped = typed.len() == word.len()
+            && typed_rest.len() >= 2
+            && typed_rest[0] == word_rest[1]
+            && typed_rest[1] == word_rest[0]
+            && typed_rest[2..] == word_rest[2..];
+        if swapped {
+            return true;
+        }
+        if typed.len().min(word.len()) < MIN_FUZZY_LEN {
+            return false;
+        }
+
+        match typed.len() as isize - word.len() as isize {
+            0 => typed_rest[1..] == word_rest[1..],
+            1 => typed_rest[1..] == *word_rest,
+            -1 => *typed_rest == word_rest[1..],
+            _ => false,
+        }
+    }
+}
 
 /// Simple keyword-based matcher that loads keywords from JSON
+/// Keywords are single words or phrases of several words, e.g. "breaking change"
 pub struct SimpleMatcher {
     keyword_map: HashMap<String, String>,
+    keywords: KeywordAutomaton,
 }
 
 impl Default for SimpleMatcher {
@@ -17,8 +242,12 @@ impl Default for SimpleMatcher {
 impl SimpleMatcher {
     pub fn new() -> Self {
         let keyword_map = Self::load_keyword_map().unwrap_or_else(|_| Self::default_keyword_map());
+        let keywords = KeywordAutomaton::new(&keyword_map);
 
-        Self { keyword_map }
+        Self {
+            keyword_map,
+            keywords,
+        }
     }
 
     /// Load keyword mapping from JSON file
@@ -47,35 +276,93 @@ impl SimpleMatcher {
         map
     }
 
+    /// Whether the message contains a known keyword, i.e. the match is not a fallback
+    /// Keywords with a typo don't count: they are a guess, like the fallback
+    pub fn has_keyword_match(&self, message: &str) -> bool {
+        self.find_first_keyword_match(message).is_some()
+    }
+
     /// Split message into words and find first matching keyword
     /// Strategy:
-    /// 1. First filter out non-alphanumeric characters (except spaces)
-    /// 2. Split the cleaned message into words
-    /// 3. Check each word in order against the keyword map
-    /// 4. Return the first match found
+    /// 1. Split the message into lowercased words (see `tokens`)
+    /// 2. Find the keywords among them by stem, single words or phrases (see `KeywordAutomaton`)
+    /// 3. Return the first match found, the longest one when several start at the same word
     fn find_first_keyword_match(&self, message: &str) -> Option<&str> {
-        // First, replace non-alphanumeric characters with spaces (except existing spaces)
-        // This converts "fix-the-bug" to "fix the bug" and "fix(auth)" to "fix auth"
+        self.keyword_hits(message)
+            .next()
+            .map(|(_, emoji_code)| emoji_code)
+    }
+
+    /// The keywords of the message, in order, with their emoji codes
+    fn keyword_hits(&self, message: &str) -> impl Iterator<Item = (String, &str)> {
+        self.keywords
+            .find(&Self::tokens(message))
+            .into_iter()
+            .filter_map(|(words, key)| {
+                let emoji_code = self.keyword_map.get(key)?;
+                Some((words, emoji_code.as_str()))
+            })
+    }
+
+    /// The keywords of the message matched despite a typo, in order, as the typed word,
+    /// the keyword and its emoji code; none when the message has an exact keyword
+    fn fuzzy_hits(&self, message: &str) -> impl Iterator<Item = (String, &str, &str)> {
+        let tokens = Self::tokens(message);
+        let fuzzy = if self.keywords.find(&tokens).is_empty() {
+            self.keywords.find_fuzzy(&tokens)
+        } else {
+            Vec::new()
+        };
+        fuzzy.into_iter().filter_map(|(typed, key)| {
+            let emoji_code = self.keyword_map.get(key)?;
+            Some((typed, key, emoji_code.as_str()))
+        })
+    }
+
+    /// Lowercased words of the message
+    /// Non-alphanumeric characters separate words, so "fix-the-bug" and "fix(auth)"
+    /// become ["fix", "the", "bug"] and ["fix", "auth"]
+    fn tokens(message: &str) -> Vec<String> {
         let cleaned_message: String = message
             .chars()
             .map(|c| if c.is_alphanumeric() { c } else { ' ' })
             .collect();
 
-        // Then extract words from the cleaned message
-        let words: Vec<String> = cleaned_message
+        cleaned_message
             .split_whitespace()
             .map(|word| word.to_lowercase())
-            .filter(|word| !word.is_empty())
-            .collect();
+            .collect()
+    }
 
-        // Find first word that exists in keyword map
-        for word in words {
-            if let Some(emoji_code) = self.keyword_map.get(&word) {
-                return Some(emoji_code);
-            }
+    /// A plain subject naming the staged files, for when nobody described the change
+    fn draft_subject(diff: &DiffSummary) -> Option<String> {
+        let paths: Vec<&str> = diff.files.iter().map(|file| file.path.as_str()).collect();
+        match paths.as_slice() {
+            [] => None,
+            [path] => Some(format!("update {path}")),
+            [first, second] => Some(format!("update {first} and {second}")),
+            [first, second, third] => Some(format!("update {first}, {second} and {third}")),
+            _ => Some(format!("update {} files", paths.len())),
         }
+    }
 
-        None
+    /// List the files and their line counts when there is more than one
+    fn draft_body(diff: &DiffSummary) -> Option<String> {
+        if diff.files.len() < 2 {
+            return None;
+        }
+        let lines: Vec<String> = diff
+            .files
+            .iter()
+            .map(|file| {
+                if file.binary {
+                    format!("- {} (binary)", file.path)
+                } else {
+                    format!("- {} (+{} -{})", file.path, file.added, file.removed)
+                }
+            })
+            .collect();
+        Some(lines.join("\n"))
     }
 }
 
@@ -86,12 +373,131 @@ impl GitmojiMatcher for SimpleMatcher {
             return Ok(Some((emoji_code.to_string(), formatted_message)));
         }
 
+        // A keyword with a typo beats the fallback
+        if let Some((_, _, emoji_code)) = self.fuzzy_hits(message).next() {
+            let formatted_message = format!("{emoji_code} {message}");
+            return Ok(Some((emoji_code.to_string(), formatted_message)));
+        }
+
         // Fallback to sparkles for general changes if no keyword match
-        let emoji_code = ":sparkles:";
+        let emoji_code = FALLBACK_CODE;
         let formatted_message = format!("{emoji_code} {message}");
         Ok(Some((emoji_code.to_string(), formatted_message)))
     }
 
+    /// Every keyword of the message votes for its code, earlier keywords counting more
+    /// Each keyword weighs half as much as the one before, so the first one always wins
+    /// as in `match_emoji`. Keywords with a typo only count without any exact keyword,
+    /// and their scores are scaled down by `FUZZY_WEIGHT`.
+    /// Without any keyword the fallback is the only candidate, scored 0.
+    fn match_candidates(&self, message: &str, k: usize) -> Result<Vec<Candidate>> {
+        let exact = self
+            .keyword_hits(message)
+            .map(|(word, emoji_code)| (emoji_code, 1.0, format!("keyword '{word}'")));
+        let fuzzy = self
+            .fuzzy_hits(message)
+            .map(|(typed, keyword, emoji_code)| {
+                let reason = format!("fuzzy keyword '{keyword}' for '{typed}'");
+                (emoji_code, FUZZY_WEIGHT, reason)
+            });
+
+        let mut weights: Vec<(String, f64, String)> = Vec::new();
+        let mut only_fuzzy = true;
+        for (rank, (emoji_code, factor, reason)) in exact.chain(fuzzy).enumerate() {
+            only_fuzzy &= factor < 1.0;
+            let weight = Candidate::rank_weight(rank) * factor;
+            match weights.iter_mut().find(|(code, ..)| code == emoji_code) {
+                Some((_, total, _)) => *total += weight,
+                None => weights.push((emoji_code.to_string(), weight, reason)),
+            }
+        }
+
+        if weights.is_empty() {
+            let fallback = Candidate {
+                code: FALLBACK_CODE.to_string(),
+                score: 0.0,
+                matcher: self.name(),
+                reason: "no keyword matched, default gitmoji".to_string(),
+            };
+            return Ok(std::iter::once(fallback).take(k).collect());
+        }
+
+        let mut candidates = Candidate::from_weights(weights, self.name(), k);
+        if only_fuzzy {
+            for candidate in &mut candidates {
+                candidate.score *= FUZZY_WEIGHT;
+            }
+        }
+        Ok(candidates)
+    }
+
+    /// List the tokens, every keyword among them, exact or fuzzy, and whether the fallback was used
+    fn explain(&self, message: &str) -> Result<Explanation> {
+        let mut explanation = Explanation::new(self.name());
+        let tokens = Self::tokens(message);
+        let stems: Vec<Cow<str>> = tokens
+            .iter()
+            .map(|token| self.keywords.stem(token))
+            .collect();
+        explanation.step(format!("tokens: {tokens:?}"));
+        explanation.step(format!("stems: {stems:?}"));
+
+        for (word, emoji_code) in self.keyword_hits(message) {
+            explanation.step(format!("'{word}' is a keyword for {emoji_code}"));
+        }
+        let fuzzy: Vec<_> = self.fuzzy_hits(message).collect();
+        for (typed, keyword, emoji_code) in &fuzzy {
+            explanation.step(format!(
+                "'{typed}' is one typo away from the keyword '{keyword}' for {emoji_code} (fuzzy)"
+            ));
+        }
+
+        match self.find_first_keyword_match(message) {
+            Some(emoji_code) => {
+                explanation.step(format!("the first keyword wins: {emoji_code}"));
+                explanation.code = Some(emoji_code.to_string());
+            }
+            None if !fuzzy.is_empty() => {
+                let (_, _, emoji_code) = fuzzy[0];
+                explanation.step(format!(
+                    "no exact keyword, the first fuzzy keyword wins: {emoji_code}"
+                ));
+                explanation.code = Some(emoji_code.to_string());
+            }
+            None => {
+                explanation.step(format!(
+                    "no keyword matched, using the {FALLBACK_CODE} fallback"
+                ));
+                explanation.code = Some(FALLBACK_CODE.to_string());
+            }
+        }
+        Ok(explanation)
+    }
+
+    /// Draft a message from the hint or the staged file names, without any LLM
+    fn generate_message(&self, context: &GenerationContext) -> Result<Option<GeneratedMessage>> {
+        let hint = context
+            .hint
+            .as_deref()
+            .map(str::trim)
+            .filter(|h| !h.is_empty());
+        let Some(subject) = hint
+            .map(str::to_string)
+            .or_else(|| Self::draft_subject(&context.diff))
+        else {
+            return Ok(None);
+        };
+
+        let Some((code, _)) = self.match_emoji(&subject)? else {
+            return Ok(None);
+        };
+        Ok(Some(GeneratedMessage {
+            code,
+            subject,
+            body: Self::draft_body(&context.diff),
+        }))
+    }
+
     fn name(&self) -> &'static str {
         "simple"
     }
@@ -314,6 +720,84 @@ mod tests {
         assert!(default_map.len() >= 10);
     }
 
+    #[test]
+    fn test_match_candidates_ranks_keywords() {
+        let matcher = SimpleMatcher::new();
+
+        let candidates = matcher
+            .match_candidates("fix docs for the new test helper", 5)
+            .unwrap();
+        let codes: Vec<&str> = candidates.iter().map(|c| c.code.as_str()).collect();
+        assert_eq!(codes[0], ":bug:");
+        assert!(codes.contains(&":memo:"));
+        assert_eq!(candidates[0].reason, "keyword 'fix'");
+        assert_eq!(candidates[0].matcher, "simple");
+
+        // The top candidate is what match_emoji picks, and scores add up to 1
+        let (code, _) = matcher
+            .match_emoji("fix docs for the new test helper")
+            .unwrap()
+            .unwrap();
+        assert_eq!(candidates[0].code, code);
+        let total: f64 = candidates.iter().map(|c| c.score).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert!(candidates.windows(2).all(|w| w[0].score >= w[1].score));
+
+        assert_eq!(
+            matcher
+                .match_candidates("fix docs for the new test helper", 1)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_match_candidates_fallback_scores_zero() {
+        let matcher = SimpleMatcher::new();
+
+        let candidates = matcher
+            .match_candidates("random unmatched message", 3)
+            .unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].code, ":sparkles:");
+        assert_eq!(candidates[0].score, 0.0);
+
+        assert!(
+            matcher
+                .match_candidates("random unmatched message", 0)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_explain() {
+        let matcher = SimpleMatcher::new();
+
+        let explanation = matcher.explain("Fix(parser): new docs").unwrap();
+        assert_eq!(explanation.matcher, "simple");
+        assert_eq!(explanation.code.as_deref(), Some(":bug:"));
+        assert_eq!(
+            explanation.steps,
+            [
+                "tokens: [\"fix\", \"parser\", \"new\", \"docs\"]",
+                "stems: [\"fix\", \"parser\", \"new\", \"doc\"]",
+                "'fix' is a keyword for :bug:",
+                "'new' is a keyword for :sparkles:",
+                "'docs' is a keyword for :memo:",
+                "the first keyword wins: :bug:",
+            ]
+        );
+
+        let explanation = matcher.explain("random unmatched message").unwrap();
+        assert_eq!(explanation.code.as_deref(), Some(":sparkles:"));
+        assert_eq!(
+            explanation.steps.last().unwrap(),
+            "no keyword matched, using the :sparkles: fallback"
+        );
+    }
+
     #[test]
     fn test_find_first_keyword_match() {
         let matcher = SimpleMatcher::new();
@@ -337,6 +821,369 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_phrase_keywords() {
+        let matcher = SimpleMatcher::new();
+
+        let test_cases = vec![
+            ("hot fix for the login page", ":ambulance:"),
+            ("Remove dead code from the parser", ":fire:"),
+            ("apply code review suggestions", ":recycle:"),
+            ("First commit", ":tada:"),
+            ("work in progress on stats", ":construction:"),
+            ("offline-support for the cache", ":airplane:"),
+            // A phrase needs all its words in order
+            ("code that is dead", ":coffin:"),
+        ];
+
+        for (message, expected) in test_cases {
+            let (code, _) = matcher.match_emoji(message).unwrap().unwrap();
+            assert_eq!(code, expected, "Wrong gitmoji for: '{message}'");
+        }
+    }
+
+    #[test]
+    fn test_inflections_match_by_stem() {
+        let matcher = SimpleMatcher::new();
+
+        let test_cases = vec![
+            ("fixes the login crash", ":bug:"),
+            ("refactors the parser", ":recycle:"),
+            ("implementing stats", ":sparkles:"),
+            ("Updated documentation", ":memo:"),
+            ("removes unused flags", ":fire:"),
+            ("hot fixes for the release", ":ambulance:"),
+            ("breaking changes in the api", ":boom:"),
+        ];
+
+        for (message, expected) in test_cases {
+            let (code, _) = matcher.match_emoji(message).unwrap().unwrap();
+            assert_eq!(code, expected, "Wrong gitmoji for: '{message}'");
+        }
+    }
+
+    #[test]
+    fn test_keyword_map_has_one_key_per_stem() {
+        let matcher = SimpleMatcher::new();
+
+        // Inflections are covered by stemming, so two keys with one stem are only
+        // needed when they map to different gitmojis
+        let mut seen: HashMap<(Vec<String>, &str), &str> = HashMap::new();
+        for (key, code) in &matcher.keyword_map {
+            let stems = SimpleMatcher::tokens(key)
+                .iter()
+                .map(|word| matcher.keywords.stem(word).into_owned())
+                .collect();
+            if let Some(other) = seen.insert((stems, code.as_str()), key.as_str()) {
+                panic!("'{key}' and '{other}' share a stem and a gitmoji");
+            }
+        }
+    }
+
+    /// Keys of the original keyword map that stemming made redundant, with their gitmoji
+    const REMOVED_INFLECTIONS: [(&str, &str); 125] = [
+        ("implemented", ":sparkles:"),
+        ("implementing", ":sparkles:"),
+        ("created", ":sparkles:"),
+        ("creating", ":sparkles:"),
+        ("introduced", ":sparkles:"),
+        ("introducing", ":sparkles:"),
+        ("fixed", ":bug:"),
+        ("fixing", ":bug:"),
+        ("resolved", ":bug:"),
+        ("resolving", ":bug:"),
+        ("corrected", ":bug:"),
+        ("correcting", ":bug:"),
+        ("solved", ":bug:"),
+        ("solving", ":bug:"),
+        ("improved", ":art:"),
+        ("improving", ":art:"),
+        ("formatted", ":art:"),
+        ("formatting", ":art:"),
+        ("structured", ":art:"),
+        ("restructured", ":art:"),
+        ("restructuring", ":art:"),
+        ("cleaned", ":art:"),
+        ("cleaning", ":art:"),
+        ("optimized", ":zap:"),
+        ("optimizing", ":zap:"),
+        ("boosted", ":zap:"),
+        ("boosting", ":zap:"),
+        ("removed", ":fire:"),
+        ("removing", ":fire:"),
+        ("deleted", ":fire:"),
+        ("deleting", ":fire:"),
+        ("dropped", ":fire:"),
+        ("dropping", ":fire:"),
+        ("eliminated", ":fire:"),
+        ("eliminating", ":fire:"),
+        ("doc", ":memo:"),
+        ("documentation", ":memo:"),
+        ("documented", ":memo:"),
+        ("documenting", ":memo:"),
+        ("commented", ":bulb:"),
+        ("commenting", ":bulb:"),
+        ("comments", ":bulb:"),
+        ("tests", ":white_check_mark:"),
+        ("testing", ":white_check_mark:"),
+        ("tested", ":white_check_mark:"),
+        ("specs", ":white_check_mark:"),
+        ("refactored", ":recycle:"),
+        ("refactoring", ":recycle:"),
+        ("deployed", ":rocket:"),
+        ("deploying", ":rocket:"),
+        ("deployment", ":rocket:"),
+        ("released", ":rocket:"),
+        ("releasing", ":rocket:"),
+        ("versions", ":bookmark:"),
+        ("tags", ":bookmark:"),
+        ("styles", ":lipstick:"),
+        ("styling", ":lipstick:"),
+        ("designed", ":lipstick:"),
+        ("designing", ":lipstick:"),
+        ("started", ":tada:"),
+        ("starting", ":tada:"),
+        ("beginning", ":tada:"),
+        ("security", ":lock:"),
+        ("secured", ":lock:"),
+        ("securing", ":lock:"),
+        ("configuration", ":wrench:"),
+        ("configured", ":wrench:"),
+        ("configuring", ":wrench:"),
+        ("dependencies", ":package:"),
+        ("deps", ":package:"),
+        ("packages", ":package:"),
+        ("upgraded", ":arrow_up:"),
+        ("upgrading", ":arrow_up:"),
+        ("bumped", ":arrow_up:"),
+        ("bumping", ":arrow_up:"),
+        ("downgraded", ":arrow_down:"),
+        ("downgrading", ":arrow_down:"),
+        ("reverted", ":rewind:"),
+        ("reverting", ":rewind:"),
+        ("merged", ":twisted_rightwards_arrows:"),
+        ("merging", ":twisted_rightwards_arrows:"),
+        ("moved", ":truck:"),
+        ("moving", ":truck:"),
+        ("renamed", ":truck:"),
+        ("renaming", ":truck:"),
+        ("breaking", ":boom:"),
+        ("breaks", ":boom:"),
+        ("typos", ":pencil2:"),
+        ("working", ":construction:"),
+        ("builds", ":construction_worker:"),
+        ("building", ":construction_worker:"),
+        ("assets", ":bento:"),
+        ("images", ":bento:"),
+        ("accessibility", ":wheelchair:"),
+        ("mocking", ":clown_face:"),
+        ("mocked", ":clown_face:"),
+        ("mocks", ":clown_face:"),
+        ("experimenting", ":alembic:"),
+        ("types", ":label:"),
+        ("typing", ":label:"),
+        ("seeds", ":seedling:"),
+        ("seeding", ":seedling:"),
+        ("flags", ":triangular_flag_on_post:"),
+        ("feature-flags", ":triangular_flag_on_post:"),
+        ("errors", ":goal_net:"),
+        ("catching", ":goal_net:"),
+        ("exceptions", ":goal_net:"),
+        ("animations", ":dizzy:"),
+        ("transitions", ":dizzy:"),
+        ("deprecated", ":wastebasket:"),
+        ("deprecating", ":wastebasket:"),
+        ("patches", ":adhesive_bandage:"),
+        ("patching", ":adhesive_bandage:"),
+        ("exploring", ":monocle_face:"),
+        ("investigation", ":monocle_face:"),
+        ("investigating", ":monocle_face:"),
+        ("failing", ":test_tube:"),
+        ("failed", ":test_tube:"),
+        ("monitoring", ":stethoscope:"),
+        ("sponsors", ":money_with_wings:"),
+        ("threading", ":thread:"),
+        ("concurrency", ":thread:"),
+        ("validation", ":safety_vest:"),
+        ("validated", ":safety_vest:"),
+        ("validating", ":safety_vest:"),
+    ];
+
+    #[test]
+    fn test_every_original_key_keeps_its_gitmoji() {
+        let matcher = SimpleMatcher::new();
+
+        let keys = matcher
+            .keyword_map
+            .iter()
+            .map(|(key, code)| (key.as_str(), code.as_str()))
+            .chain(REMOVED_INFLECTIONS);
+        for (key, code) in keys {
+            assert_eq!(
+                matcher.find_first_keyword_match(key),
+                Some(code),
+                "Wrong gitmoji for: '{key}'"
+            );
+        }
+    }
+
+    #[test]
+    fn test_ambiguous_stems_match_closest_key() {
+        let matcher = SimpleMatcher::new();
+
+        // "experience" and "experiment" share a stem but not a gitmoji
+        assert_eq!(
+            matcher.find_first_keyword_match("smoother onboarding experience"),
+            Some(":technologist:")
+        );
+        assert_eq!(
+            matcher.find_first_keyword_match("experiment with caching"),
+            Some(":alembic:")
+        );
+        // Their inflections go to the key they share the most letters with
+        for (message, expected) in [
+            ("shared experiences", ":technologist:"),
+            ("experimenting with caching", ":alembic:"),
+            ("experimented with caching", ":alembic:"),
+            ("more experiments", ":alembic:"),
+        ] {
+            assert_eq!(
+                matcher.find_first_keyword_match(message),
+                Some(expected),
+                "Wrong gitmoji for: '{message}'"
+            );
+        }
+    }
+
+    #[test]
+    fn test_longest_keyword_wins() {
+        let matcher = SimpleMatcher::new();
+
+        let hits: Vec<(String, &str)> = matcher
+            .keyword_hits("breaking change: drop the v1 api after a hot fix")
+            .collect();
+        assert_eq!(
+            hits,
+            [
+                ("breaking change".to_string(), ":boom:"),
+                ("drop".to_string(), ":fire:"),
+                ("hot fix".to_string(), ":ambulance:"),
+            ]
+        );
+
+        // "fix" alone is still a keyword, and "hot" alone is none
+        assert_eq!(
+            matcher.find_first_keyword_match("hot fix"),
+            Some(":ambulance:")
+        );
+        assert_eq!(matcher.find_first_keyword_match("a fix"), Some(":bug:"));
+        assert_eq!(matcher.find_first_keyword_match("hot water"), None);
+    }
+
+    fn one_typo_apart(typed: &str, word: &str) -> bool {
+        let typed: Vec<char> = typed.chars().collect();
+        let word: Vec<char> = word.chars().collect();
+        KeywordAutomaton::one_typo_apart(&typed, &word)
+    }
+
+    #[test]
+    fn test_one_typo_apart() {
+        // Two neighbouring letters swapped, in any word of three letters or more
+        assert!(one_typo_apart("fxi", "fix"));
+        assert!(one_typo_apart("refacotr", "refactor"));
+        assert!(one_typo_apart("tets", "test"));
+        assert!(!one_typo_apart("ti", "it"));
+        // One letter left out, added or replaced, in words of four letters or more
+        assert!(one_typo_apart("optimze", "optimize"));
+        assert!(one_typo_apart("deployy", "deploy"));
+        assert!(one_typo_apart("upgrafe", "upgrade"));
+        assert!(one_typo_apart("tesr", "test"));
+        assert!(one_typo_apart("bulid", "build"));
+        assert!(!one_typo_apart("fox", "fix"));
+        assert!(!one_typo_apart("fixx", "fix"));
+        // Not a typo
+        assert!(!one_typo_apart("fix", "fix"));
+        assert!(!one_typo_apart("refcatro", "refactor"));
+        assert!(!one_typo_apart("optmze", "optimize"));
+    }
+
+    #[test]
+    fn test_fuzzy_keywords() {
+        let matcher = SimpleMatcher::new();
+
+        let test_cases = vec![
+            ("fxi login", ":bug:"),
+            ("mrege upstream", ":twisted_rightwards_arrows:"),
+            ("refacotr the parser", ":recycle:"),
+            ("optimze startup", ":zap:"),
+        ];
+        for (message, expected) in test_cases {
+            let (code, _) = matcher.match_emoji(message).unwrap().unwrap();
+            assert_eq!(code, expected, "Wrong gitmoji for: '{message}'");
+            // A keyword with a typo is a guess, not a keyword match
+            assert!(!matcher.has_keyword_match(message));
+        }
+
+        // An exact keyword wins over an earlier one with a typo
+        let (code, _) = matcher.match_emoji("refacotr and fix").unwrap().unwrap();
+        assert_eq!(code, ":bug:");
+
+        // Common words close to a keyword are not typos of it
+        for message in ["rules", "random unmatched text", "show the login page"] {
+            let (code, _) = matcher.match_emoji(message).unwrap().unwrap();
+            assert_eq!(code, ":sparkles:", "Wrong gitmoji for: '{message}'");
+        }
+    }
+
+    #[test]
+    fn test_common_words_are_near_keywords() {
+        let matcher = SimpleMatcher::new();
+
+        // Each common word is listed because a keyword is one typo away
+        for common in COMMON_WORDS.iter() {
+            let typed: Vec<char> = common.chars().collect();
+            assert!(
+                matcher
+                    .keywords
+                    .words
+                    .iter()
+                    .any(|(word, _)| KeywordAutomaton::one_typo_apart(&typed, word)),
+                "'{common}' is no typo away from any keyword"
+            );
+            assert!(
+                !matcher.has_keyword_match(common),
+                "'{common}' is a keyword"
+            );
+        }
+    }
+
+    #[test]
+    fn test_match_candidates_fuzzy_only_without_exact() {
+        let matcher = SimpleMatcher::new();
+
+        // An exact keyword anywhere rules out guessing at typos
+        let candidates = matcher.match_candidates("refacotr and fix", 3).unwrap();
+        let codes: Vec<&str> = candidates.iter().map(|c| c.code.as_str()).collect();
+        assert_eq!(codes, [":bug:"]);
+
+        let candidates = matcher.match_candidates("refacotr the parser", 3).unwrap();
+        assert_eq!(
+            candidates[0].reason,
+            "fuzzy keyword 'refactor' for 'refacotr'"
+        );
+
+        // Alone, a fuzzy keyword is still less certain than an exact one
+        let candidates = matcher.match_candidates("optimze startup", 3).unwrap();
+        assert_eq!(candidates[0].code, ":zap:");
+        assert!(candidates[0].score > 0.0 && candidates[0].score < 1.0);
+
+        let explanation = matcher.explain("optimze startup").unwrap();
+        assert_eq!(explanation.code.as_deref(), Some(":zap:"));
+        assert!(explanation.steps.contains(
+            &"'optimze' is one typo away from the keyword 'optimize' for :zap: (fuzzy)".to_string()
+        ));
+    }
+
     #[test]
     fn test_result_format() {
         let matcher = SimpleMatcher::new();
@@ -357,6 +1204,60 @@ mod tests {
         assert_eq!(format_message, ":sparkles: random unmatched text");
     }
 
+    #[test]
+    fn test_generate_message_drafts_from_files() {
+        let matcher = SimpleMatcher::new();
+        let diff = |paths: &[&str]| DiffSummary {
+            files: paths
+                .iter()
+                .map(|path| crate::diff::summary::FileDiff {
+                    path: path.to_string(),
+                    added: 2,
+                    removed: 1,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        let context = GenerationContext {
+            diff: diff(&["src/lib.rs"]),
+            ..Default::default()
+        };
+        let message = matcher.generate_message(&context).unwrap().unwrap();
+        assert_eq!(message.subject, "update src/lib.rs");
+        assert_eq!(message.body, None);
+
+        let context = GenerationContext {
+            diff: diff(&["src/a.rs", "src/b.rs", "src/c.rs", "src/d.rs"]),
+            hint: Some("fix parser crash".to_string()),
+            ..Default::default()
+        };
+        let message = matcher.generate_message(&context).unwrap().unwrap();
+        assert_eq!(
+            message.to_message().lines().next(),
+            Some(":bug: fix parser crash")
+        );
+        assert_eq!(
+            message.body.unwrap().lines().next(),
+            Some("- src/a.rs (+2 -1)")
+        );
+
+        let context = GenerationContext {
+            diff: diff(&["a", "b", "c", "d", "e"]),
+            ..Default::default()
+        };
+        let message = matcher.generate_message(&context).unwrap().unwrap();
+        assert_eq!(message.subject, "update 5 files");
+
+        assert_eq!(
+            matcher
+                .generate_message(&GenerationContext::default())
+                .unwrap(),
+            None
+        );
+    }
+
     #[test]
     fn test_unicode_and_special_characters() {
         let matcher = SimpleMatcher::new();
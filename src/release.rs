@@ -0,0 +1,318 @@
+use crate::{
+    commit::CommitInfo,
+    diff::breaking::BreakingChange,
+    emoji::{EmojiLookup, SemverImpact},
+    lint::{CommitLinter, SubjectGitmoji},
+};
+use anyhow::{Result, bail};
+use serde::Serialize;
+use std::fmt;
+
+/// A `major.minor.patch` version as used in release tags
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl Version {
+    /// Parse "1.2.3" or "v1.2.3"; pre-releases and build metadata are not release versions
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.strip_prefix('v').unwrap_or(text);
+        let mut parts = text.split('.').map(|part| {
+            // Leading zeros and signs are not valid semver
+            let valid = part.chars().all(|c| c.is_ascii_digit())
+                && !part.is_empty()
+                && (part == "0" || !part.starts_with('0'));
+            valid.then(|| part.parse().ok()).flatten()
+        });
+
+        let version = Version {
+            major: parts.next()??,
+            minor: parts.next()??,
+            patch: parts.next()??,
+        };
+        parts.next().is_none().then_some(version)
+    }
+
+    /// The version after a release with the given impact
+    /// Before 1.0.0 breaking changes only bump the minor version, as Cargo treats 0.x
+    pub fn bump(self, impact: SemverImpact) -> Self {
+        match impact {
+            SemverImpact::Major if self.major > 0 => Version {
+                major: self.major + 1,
+                minor: 0,
+                patch: 0,
+            },
+            SemverImpact::Major | SemverImpact::Minor => Version {
+                minor: self.minor + 1,
+                patch: 0,
+                ..self
+            },
+            SemverImpact::Patch => Version {
+                patch: self.patch + 1,
+                ..self
+            },
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl Serialize for Version {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// A commit that moves the version
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BumpReason {
+    pub sha: String,
+    pub subject: String,
+    pub impact: SemverImpact,
+}
+
+/// The next release computed from the commits since the latest release tag
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReleasePlan {
+    /// The latest release tag, None when there is none yet
+    pub tag: Option<String>,
+    pub current: Version,
+    /// The largest impact of any commit, None when no commit changes the version
+    pub impact: Option<SemverImpact>,
+    pub next: Version,
+    /// Commits that change the version, largest impact first
+    pub commits: Vec<BumpReason>,
+}
+
+impl ReleasePlan {
+    /// The tag with the highest release version
+    pub fn latest_release(tags: &[String]) -> Option<(&str, Version)> {
+        tags.iter()
+            .filter_map(|tag| Some((tag.as_str(), Version::parse(tag)?)))
+            .max_by_key(|(_, version)| *version)
+    }
+
+    /// Classify the commits since `tag`, newest first as `git log` lists them
+    pub fn from_commits(tag: Option<(&str, Version)>, commits: &[CommitInfo]) -> Self {
+        let mut reasons: Vec<BumpReason> = commits
+            .iter()
+            .filter(|commit| !commit.is_merge() && !CommitLinter::is_exempt(&commit.subject))
+            .filter_map(|commit| {
+                Some(BumpReason {
+                    sha: commit.sha.clone(),
+                    subject: commit.subject.clone(),
+                    impact: Self::commit_impact(&commit.message())?,
+                })
+            })
+            .collect();
+        reasons.sort_by_key(|reason| std::cmp::Reverse(reason.impact));
+
+        let current = tag.map(|(_, version)| version).unwrap_or_default();
+        let impact = reasons.first().map(|reason| reason.impact);
+        Self {
+            tag: tag.map(|(tag, _)| tag.to_string()),
+            current,
+            impact,
+            next: impact.map_or(current, |impact| current.bump(impact)),
+            commits: reasons,
+        }
+    }
+
+    /// The impact of one commit message: its gitmoji's semver field, or major for a `!`
+    /// marker in the subject or a `BREAKING CHANGE:` trailer in the body
+    pub fn commit_impact(message: &str) -> Option<SemverImpact> {
+        let (subject, body) = message.split_once('\n').unwrap_or((message, ""));
+        let gitmoji = SubjectGitmoji::parse(subject);
+        let text = match gitmoji {
+            SubjectGitmoji::Valid { text, .. } => text,
+            _ => subject,
+        };
+
+        let breaking =
+            BreakingChange::message_signal(&format!("{text}\n{body}")).map(|_| SemverImpact::Major);
+        let declared = gitmoji.code().and_then(EmojiLookup::semver);
+        breaking.max(declared)
+    }
+
+    /// The tag for the next version, keeping the `v` prefix of the latest tag
+    pub fn next_tag(&self) -> String {
+        match &self.tag {
+            Some(tag) if !tag.starts_with('v') => self.next.to_string(),
+            _ => format!("v{}", self.next),
+        }
+    }
+}
+
+/// Replace the `version` of the `[package]` section of a Cargo.toml, keeping everything else
+pub fn set_cargo_version(contents: &str, version: Version) -> Result<String> {
+    let mut output = String::with_capacity(contents.len());
+    let mut section = "";
+    let mut replaced = false;
+
+    for line in contents.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            section = trimmed;
+        } else if section == "[package]"
+            && !replaced
+            && let Some(rest) = trimmed.strip_prefix("version")
+            && let Some(value) = rest.trim_start().strip_prefix('=')
+            && value.trim_start().starts_with('"')
+            && let Some(open) = line.find('"')
+            && let Some(close) = line[open + 1..].find('"')
+        {
+            output.push_str(&line[..=open]);
+            output.push_str(&version.to_string());
+            output.push_str(&line[open + 1 + close..]);
+            replaced = true;
+            continue;
+        }
+        output.push_str(line);
+    }
+
+    if !replaced {
+        bail!(
+            "No `version = \"...\"` in the [package] section (inherited versions are not supported)"
+        );
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(sha: &str, subject: &str) -> CommitInfo {
+        CommitInfo {
+            sha: sha.to_string(),
+            parents: vec!["0000000".to_string()],
+            subject: subject.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn version(major: u64, minor: u64, patch: u64) -> Version {
+        Version {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    #[test]
+    fn test_version_parse() {
+        assert_eq!(Version::parse("v1.2.3"), Some(version(1, 2, 3)));
+        assert_eq!(Version::parse("0.10.0"), Some(version(0, 10, 0)));
+        assert_eq!(Version::parse("v1.2"), None);
+        assert_eq!(Version::parse("v1.2.3-rc.1"), None);
+        assert_eq!(Version::parse("v1.02.3"), None);
+        assert_eq!(Version::parse("release-1"), None);
+        assert_eq!(version(1, 2, 3).to_string(), "1.2.3");
+    }
+
+    #[test]
+    fn test_version_bump() {
+        let v = version(1, 2, 3);
+        assert_eq!(v.bump(SemverImpact::Major), version(2, 0, 0));
+        assert_eq!(v.bump(SemverImpact::Minor), version(1, 3, 0));
+        assert_eq!(v.bump(SemverImpact::Patch), version(1, 2, 4));
+
+        // Breaking changes before 1.0.0 bump the minor version
+        assert_eq!(version(0, 4, 1).bump(SemverImpact::Major), version(0, 5, 0));
+    }
+
+    #[test]
+    fn test_latest_release_picks_highest_version() {
+        let tags: Vec<String> = ["v1.10.0", "v1.9.0", "nightly", "v2.0.0-rc.1"]
+            .iter()
+            .map(|tag| tag.to_string())
+            .collect();
+        assert_eq!(
+            ReleasePlan::latest_release(&tags),
+            Some(("v1.10.0", version(1, 10, 0)))
+        );
+        assert_eq!(ReleasePlan::latest_release(&[]), None);
+    }
+
+    #[test]
+    fn test_commit_impact() {
+        assert_eq!(
+            ReleasePlan::commit_impact(":sparkles: add stats"),
+            Some(SemverImpact::Minor)
+        );
+        assert_eq!(
+            ReleasePlan::commit_impact("🐛 fix crash"),
+            Some(SemverImpact::Patch)
+        );
+        assert_eq!(ReleasePlan::commit_impact(":memo: update docs"), None);
+        assert_eq!(
+            ReleasePlan::commit_impact(":sparkles: feat(api)!: drop v1"),
+            Some(SemverImpact::Major)
+        );
+        assert_eq!(ReleasePlan::commit_impact("tweak things"), None);
+        assert_eq!(
+            ReleasePlan::commit_impact(
+                ":sparkles: add stats\n\nBREAKING CHANGE: `--since` is now required"
+            ),
+            Some(SemverImpact::Major)
+        );
+    }
+
+    #[test]
+    fn test_release_plan() {
+        let commits = vec![
+            commit("aaaaaaa", ":bug: fix crash"),
+            commit("bbbbbbb", ":memo: update docs"),
+            commit("ccccccc", ":sparkles: add stats"),
+            commit("ddddddd", "fixup! :boom: drop v1"),
+        ];
+        let plan = ReleasePlan::from_commits(Some(("v1.2.3", version(1, 2, 3))), &commits);
+
+        assert_eq!(plan.impact, Some(SemverImpact::Minor));
+        assert_eq!(plan.next, version(1, 3, 0));
+        assert_eq!(plan.next_tag(), "v1.3.0");
+        let shas: Vec<&str> = plan.commits.iter().map(|c| c.sha.as_str()).collect();
+        assert_eq!(shas, ["ccccccc", "aaaaaaa"]);
+
+        let plan = ReleasePlan::from_commits(None, &commits[1..2]);
+        assert_eq!(plan.impact, None);
+        assert_eq!(plan.next, Version::default());
+
+        let plan = ReleasePlan::from_commits(Some(("2.0.0", version(2, 0, 0))), &commits);
+        assert_eq!(plan.next_tag(), "2.1.0");
+    }
+
+    #[test]
+    fn test_release_plan_breaking_trailer_in_body() {
+        let mut breaking = commit("eeeeeee", ":sparkles: add stats");
+        breaking.body = "BREAKING CHANGE: `--since` is now required".to_string();
+        let commits = vec![commit("aaaaaaa", ":bug: fix crash"), breaking];
+
+        let plan = ReleasePlan::from_commits(Some(("v1.2.0", version(1, 2, 0))), &commits);
+        assert_eq!(plan.impact, Some(SemverImpact::Major));
+        assert_eq!(plan.next_tag(), "v2.0.0");
+        assert_eq!(plan.commits[0].sha, "eeeeeee");
+        assert_eq!(plan.commits[0].subject, ":sparkles: add stats");
+    }
+
+    #[test]
+    fn test_set_cargo_version() {
+        let manifest = "[package]\nname = \"amoji\"\nversion = \"0.1.2\" # keep me\n\n[dependencies]\nversion = \"1\"\n";
+        assert_eq!(
+            set_cargo_version(manifest, version(0, 2, 0)).unwrap(),
+            "[package]\nname = \"amoji\"\nversion = \"0.2.0\" # keep me\n\n[dependencies]\nversion = \"1\"\n"
+        );
+
+        assert!(
+            set_cargo_version("[package]\nversion.workspace = true\n", version(1, 0, 0)).is_err()
+        );
+        assert!(set_cargo_version("[workspace]\nmembers = []\n", version(1, 0, 0)).is_err());
+    }
+}
@@ -0,0 +1,131 @@
+use crate::lint::{CommitLinter, SubjectGitmoji};
+
+/// Gitmoji for concluding a merge
+const MERGE_CODE: &str = ":twisted_rightwards_arrows:";
+
+/// Gitmoji for the subjects `git revert` writes
+const REVERT_CODE: &str = ":rewind:";
+
+/// Operations in progress in the repository that shape the next commit
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepoState {
+    /// A merge is being concluded (MERGE_HEAD exists)
+    pub merging: bool,
+    /// Subject of the commit being cherry-picked (CHERRY_PICK_HEAD exists)
+    pub cherry_pick: Option<String>,
+}
+
+/// A commit amoji must not treat as a freshly typed message
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpecialCommit {
+    /// A fixup!/squash!/amend! subject; a prefix would break `rebase --autosquash`
+    Autosquash,
+    /// The commit concludes a merge
+    Merge,
+    /// The commit finishes a cherry-pick and keeps the gitmoji of the picked commit, if any
+    CherryPick { code: Option<&'static str> },
+    /// A `Revert "..."` subject as written by `git revert`
+    Revert,
+}
+
+impl SpecialCommit {
+    /// Detect a special commit from its subject and the operations in progress
+    pub fn detect(subject: &str, state: &RepoState) -> Option<Self> {
+        let subject = subject.trim();
+        if CommitLinter::is_autosquash(subject) {
+            Some(SpecialCommit::Autosquash)
+        } else if state.merging {
+            Some(SpecialCommit::Merge)
+        } else if let Some(picked) = &state.cherry_pick {
+            Some(SpecialCommit::CherryPick {
+                code: SubjectGitmoji::parse(picked).code(),
+            })
+        } else if subject.starts_with("Revert \"") {
+            Some(SpecialCommit::Revert)
+        } else {
+            None
+        }
+    }
+
+    /// The gitmoji to prefix, None when the subject must be committed as-is
+    pub fn code(&self) -> Option<&'static str> {
+        match self {
+            SpecialCommit::Autosquash => None,
+            SpecialCommit::Merge => Some(MERGE_CODE),
+            SpecialCommit::CherryPick { code } => *code,
+            SpecialCommit::Revert => Some(REVERT_CODE),
+        }
+    }
+
+    /// Why the subject is special, for display
+    pub fn reason(&self) -> &'static str {
+        match self {
+            SpecialCommit::Autosquash => "fixup!/squash!/amend! commits keep their subject",
+            SpecialCommit::Merge => "a merge is in progress",
+            SpecialCommit::CherryPick { code: Some(_) } => {
+                "a cherry-pick is in progress, keeping the picked commit's gitmoji"
+            }
+            SpecialCommit::CherryPick { code: None } => {
+                "a cherry-pick is in progress and the picked commit has no gitmoji"
+            }
+            SpecialCommit::Revert => "the commit reverts another one",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_from_subject() {
+        let clean = RepoState::default();
+
+        for subject in ["fixup! add stats", "squash! :bug: fix", "amend! fix login"] {
+            let special = SpecialCommit::detect(subject, &clean);
+            assert_eq!(special, Some(SpecialCommit::Autosquash), "{subject}");
+            assert_eq!(special.unwrap().code(), None);
+        }
+
+        let revert = SpecialCommit::detect("Revert \"add stats\"", &clean).unwrap();
+        assert_eq!(revert, SpecialCommit::Revert);
+        assert_eq!(revert.code(), Some(":rewind:"));
+
+        assert_eq!(
+            SpecialCommit::detect("revert the parser change", &clean),
+            None
+        );
+        assert_eq!(SpecialCommit::detect("add stats", &clean), None);
+    }
+
+    #[test]
+    fn test_detect_from_state() {
+        let merging = RepoState {
+            merging: true,
+            ..Default::default()
+        };
+        let merge = SpecialCommit::detect("Merge branch 'topic'", &merging).unwrap();
+        assert_eq!(merge.code(), Some(":twisted_rightwards_arrows:"));
+        // A fixup is never prefixed, even while merging
+        assert_eq!(
+            SpecialCommit::detect("fixup! add stats", &merging),
+            Some(SpecialCommit::Autosquash)
+        );
+
+        let picking = RepoState {
+            cherry_pick: Some("🐛 fix login".to_string()),
+            ..Default::default()
+        };
+        let pick = SpecialCommit::detect("fix login", &picking).unwrap();
+        assert_eq!(pick.code(), Some(":bug:"));
+
+        let picking = RepoState {
+            cherry_pick: Some("fix login".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            SpecialCommit::detect("fix login", &picking),
+            Some(SpecialCommit::CherryPick { code: None })
+        );
+    }
+}
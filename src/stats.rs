@@ -0,0 +1,524 @@
+use crate::{
+    changelog::ChangelogSection,
+    commit::CommitInfo,
+    lint::{CommitLinter, SubjectGitmoji},
+};
+use anyhow::Result;
+use serde::Serialize;
+use std::{collections::HashMap, fmt::Write};
+
+/// Seconds in a day, for bucketing author dates
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Length of a trend period
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Period {
+    /// ISO 8601 weeks, e.g. "2026-W03"
+    Week,
+    /// Calendar months, e.g. "2026-01"
+    Month,
+}
+
+impl Period {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Period::Week => "week",
+            Period::Month => "month",
+        }
+    }
+
+    /// The period containing a Unix timestamp, in UTC
+    /// Labels sort chronologically as plain strings
+    pub fn label(self, timestamp: i64) -> String {
+        let days = timestamp.div_euclid(SECONDS_PER_DAY);
+        match self {
+            Period::Month => {
+                let (year, month, _) = civil_from_days(days);
+                format!("{year}-{month:02}")
+            }
+            Period::Week => {
+                // ISO weeks belong to the year of their Thursday; 1970-01-01 was a Thursday
+                let weekday = (days + 3).rem_euclid(7);
+                let thursday = days - weekday + 3;
+                let (year, _, _) = civil_from_days(thursday);
+                let week = (thursday - days_from_civil(year, 1, 1)) / 7 + 1;
+                format!("{year}-W{week:02}")
+            }
+        }
+    }
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Proleptic Gregorian date of a number of days since 1970-01-01
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// How many commits used one gitmoji
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CodeCount {
+    pub code: &'static str,
+    pub commits: usize,
+}
+
+/// How many commits fell into one changelog section
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SectionCount {
+    pub section: ChangelogSection,
+    pub commits: usize,
+}
+
+/// Gitmoji usage of a set of commits
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Usage {
+    pub commits: usize,
+    pub without_gitmoji: usize,
+    /// Gitmojis used, most used first
+    pub codes: Vec<CodeCount>,
+    /// Changelog sections with at least one commit, in changelog order
+    /// Commits without a gitmoji are counted under Other, as in the changelog
+    pub sections: Vec<SectionCount>,
+}
+
+impl Usage {
+    fn add(&mut self, code: Option<&'static str>) {
+        self.commits += 1;
+        match code {
+            Some(code) => match self.codes.iter_mut().find(|count| count.code == code) {
+                Some(count) => count.commits += 1,
+                None => self.codes.push(CodeCount { code, commits: 1 }),
+            },
+            None => self.without_gitmoji += 1,
+        }
+
+        let section = ChangelogSection::for_code(code);
+        match self
+            .sections
+            .iter_mut()
+            .find(|count| count.section == section)
+        {
+            Some(count) => count.commits += 1,
+            None => self.sections.push(SectionCount {
+                section,
+                commits: 1,
+            }),
+        }
+    }
+
+    fn sort(&mut self) {
+        self.codes
+            .sort_by(|a, b| b.commits.cmp(&a.commits).then(a.code.cmp(b.code)));
+        self.sections.sort_by_key(|count| {
+            ChangelogSection::ALL
+                .iter()
+                .position(|section| *section == count.section)
+        });
+    }
+
+    /// Commits in a section
+    pub fn section(&self, section: ChangelogSection) -> usize {
+        self.sections
+            .iter()
+            .find(|count| count.section == section)
+            .map_or(0, |count| count.commits)
+    }
+
+    /// Share of the commits without a gitmoji, in percent
+    pub fn without_gitmoji_percent(&self) -> f64 {
+        percent(self.without_gitmoji, self.commits)
+    }
+}
+
+fn percent(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+/// Gitmoji usage of one author
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AuthorUsage {
+    pub author: String,
+    #[serde(flatten)]
+    pub usage: Usage,
+}
+
+/// Gitmoji usage during one week or month
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PeriodUsage {
+    pub period: String,
+    #[serde(flatten)]
+    pub usage: Usage,
+}
+
+/// Gitmoji usage over a range of history
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RepoStats {
+    pub range: String,
+    #[serde(flatten)]
+    pub usage: Usage,
+    /// Authors, most commits first
+    pub authors: Vec<AuthorUsage>,
+    pub period: Period,
+    /// Periods with at least one commit, oldest first
+    pub trend: Vec<PeriodUsage>,
+}
+
+impl RepoStats {
+    /// Count the gitmojis of commits as `git log` lists them
+    /// Merges and fixup!/squash!/amend! commits are left out, as in the changelog
+    pub fn from_commits(commits: &[CommitInfo], range: &str, period: Period) -> Self {
+        let mut usage = Usage::default();
+        let mut authors: HashMap<&str, Usage> = HashMap::new();
+        let mut trend: HashMap<String, Usage> = HashMap::new();
+
+        for commit in commits {
+            if commit.is_merge() || CommitLinter::is_exempt(&commit.subject) {
+                continue;
+            }
+
+            let code = SubjectGitmoji::parse(&commit.subject).code();
+            usage.add(code);
+            authors.entry(&commit.author).or_default().add(code);
+            trend
+                .entry(period.label(commit.timestamp))
+                .or_default()
+                .add(code);
+        }
+
+        usage.sort();
+        let mut authors: Vec<AuthorUsage> = authors
+            .into_iter()
+            .map(|(author, mut usage)| {
+                usage.sort();
+                AuthorUsage {
+                    author: author.to_string(),
+                    usage,
+                }
+            })
+            .collect();
+        authors.sort_by(|a, b| {
+            b.usage
+                .commits
+                .cmp(&a.usage.commits)
+                .then_with(|| a.author.cmp(&b.author))
+        });
+        let mut trend: Vec<PeriodUsage> = trend
+            .into_iter()
+            .map(|(period, mut usage)| {
+                usage.sort();
+                PeriodUsage { period, usage }
+            })
+            .collect();
+        trend.sort_by(|a, b| a.period.cmp(&b.period));
+
+        Self {
+            range: range.to_string(),
+            usage,
+            authors,
+            period,
+            trend,
+        }
+    }
+
+    /// Plain-text tables: gitmoji counts, authors and the trend per period
+    pub fn to_table(&self) -> String {
+        let usage = &self.usage;
+        let mut output = format!(
+            "{}: {} commits, {} without gitmoji ({:.1}%)\n",
+            self.range,
+            usage.commits,
+            usage.without_gitmoji,
+            usage.without_gitmoji_percent()
+        );
+        if usage.commits == 0 {
+            return output;
+        }
+
+        writeln!(
+            output,
+            "\n{:<28} {:>7} {:>6}",
+            "Gitmoji", "Commits", "Share"
+        )
+        .unwrap();
+        for count in &usage.codes {
+            writeln!(
+                output,
+                "{:<28} {:>7} {:>5.1}%",
+                count.code,
+                count.commits,
+                percent(count.commits, usage.commits)
+            )
+            .unwrap();
+        }
+        if usage.without_gitmoji > 0 {
+            writeln!(
+                output,
+                "{:<28} {:>7} {:>5.1}%",
+                "(none)",
+                usage.without_gitmoji,
+                usage.without_gitmoji_percent()
+            )
+            .unwrap();
+        }
+
+        writeln!(
+            output,
+            "\n{:<24} {:>7} {:>10}  Top gitmoji",
+            "Author", "Commits", "No gitmoji"
+        )
+        .unwrap();
+        for author in &self.authors {
+            let top = author.usage.codes.first().map_or("-", |count| count.code);
+            writeln!(
+                output,
+                "{:<24} {:>7} {:>9.1}%  {top}",
+                author.author,
+                author.usage.commits,
+                author.usage.without_gitmoji_percent()
+            )
+            .unwrap();
+        }
+
+        let period = match self.period {
+            Period::Week => "Week",
+            Period::Month => "Month",
+        };
+        writeln!(
+            output,
+            "\n{period:<10} {:>7} {:>8} {:>6} {:>10}",
+            "Commits", "Features", "Fixes", "No gitmoji"
+        )
+        .unwrap();
+        for entry in &self.trend {
+            writeln!(
+                output,
+                "{:<10} {:>7} {:>8} {:>6} {:>10}",
+                entry.period,
+                entry.usage.commits,
+                entry.usage.section(ChangelogSection::Features),
+                entry.usage.section(ChangelogSection::Fixes),
+                entry.usage.without_gitmoji
+            )
+            .unwrap();
+        }
+
+        output
+    }
+
+    /// The statistics as pretty-printed JSON
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// One `group,name,gitmoji,commits` row per count, for spreadsheets
+    /// The group is "total", "author", "week" or "month"; commits without gitmoji use "none"
+    pub fn to_csv(&self) -> String {
+        let mut output = String::from("group,name,gitmoji,commits\n");
+        let mut write_usage = |group: &str, name: &str, usage: &Usage| {
+            for count in &usage.codes {
+                writeln!(
+                    output,
+                    "{group},{},{},{}",
+                    csv_field(name),
+                    count.code,
+                    count.commits
+                )
+                .unwrap();
+            }
+            if usage.without_gitmoji > 0 {
+                writeln!(
+                    output,
+                    "{group},{},none,{}",
+                    csv_field(name),
+                    usage.without_gitmoji
+                )
+                .unwrap();
+            }
+        };
+
+        write_usage("total", "", &self.usage);
+        for author in &self.authors {
+            write_usage("author", &author.author, &author.usage);
+        }
+        for entry in &self.trend {
+            write_usage(self.period.as_str(), &entry.period, &entry.usage);
+        }
+        output
+    }
+}
+
+/// Quote a CSV field when it contains a separator, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2026-01-05 12:00:00 UTC, a Monday
+    const MONDAY: i64 = 1_767_614_400;
+
+    fn commit(author: &str, timestamp: i64, subject: &str) -> CommitInfo {
+        CommitInfo {
+            sha: format!("{timestamp:x}"),
+            parents: vec!["0000000".to_string()],
+            author: author.to_string(),
+            timestamp,
+            subject: subject.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn sample() -> RepoStats {
+        let day = SECONDS_PER_DAY;
+        let commits = vec![
+            commit("Ada", MONDAY + 30 * day, ":bug: fix crash"),
+            commit("Grace", MONDAY + 29 * day, "🐛 fix login"),
+            commit("Ada", MONDAY + 8 * day, ":sparkles: add stats"),
+            commit("Ada", MONDAY + day, "tweak things"),
+            commit("Grace", MONDAY, ":sparkles: add log"),
+            commit("Grace", MONDAY, "fixup! :sparkles: add log"),
+            CommitInfo {
+                parents: vec!["1".to_string(), "2".to_string()],
+                ..commit("Ada", MONDAY, "Merge branch 'topic'")
+            },
+        ];
+        RepoStats::from_commits(&commits, "HEAD", Period::Month)
+    }
+
+    #[test]
+    fn test_period_label() {
+        assert_eq!(Period::Month.label(MONDAY), "2026-01");
+        assert_eq!(Period::Week.label(MONDAY), "2026-W02");
+        assert_eq!(Period::Month.label(0), "1970-01");
+        assert_eq!(Period::Week.label(0), "1970-W01");
+
+        // 2027-01-01 is a Friday, so it still belongs to the last week of 2026
+        let new_year = days_from_civil(2027, 1, 1) * SECONDS_PER_DAY;
+        assert_eq!(Period::Week.label(new_year), "2026-W53");
+        // 2024-12-30 is a Monday in the first week of 2025
+        let monday = days_from_civil(2024, 12, 30) * SECONDS_PER_DAY;
+        assert_eq!(Period::Week.label(monday), "2025-W01");
+        assert_eq!(Period::Month.label(monday), "2024-12");
+    }
+
+    #[test]
+    fn test_civil_round_trip() {
+        for days in [-719_468, -1, 0, 59, 60, 11_016, 20_458, 2_932_896] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+        assert_eq!(civil_from_days(days_from_civil(2024, 2, 29)), (2024, 2, 29));
+    }
+
+    #[test]
+    fn test_from_commits_counts_usage() {
+        let stats = sample();
+
+        assert_eq!(stats.usage.commits, 5);
+        assert_eq!(stats.usage.without_gitmoji, 1);
+        assert_eq!(
+            stats.usage.codes,
+            [
+                CodeCount {
+                    code: ":bug:",
+                    commits: 2
+                },
+                CodeCount {
+                    code: ":sparkles:",
+                    commits: 2
+                }
+            ]
+        );
+        assert_eq!(stats.usage.section(ChangelogSection::Features), 2);
+        assert_eq!(stats.usage.section(ChangelogSection::Fixes), 2);
+        assert_eq!(stats.usage.section(ChangelogSection::Other), 1);
+
+        let authors: Vec<(&str, usize)> = stats
+            .authors
+            .iter()
+            .map(|a| (a.author.as_str(), a.usage.commits))
+            .collect();
+        assert_eq!(authors, [("Ada", 3), ("Grace", 2)]);
+
+        let trend: Vec<(&str, usize)> = stats
+            .trend
+            .iter()
+            .map(|p| (p.period.as_str(), p.usage.commits))
+            .collect();
+        assert_eq!(trend, [("2026-01", 3), ("2026-02", 2)]);
+        assert_eq!(stats.trend[1].usage.section(ChangelogSection::Fixes), 2);
+
+        let empty = RepoStats::from_commits(&[], "HEAD", Period::Week);
+        assert_eq!(empty.usage.without_gitmoji_percent(), 0.0);
+        assert!(empty.to_table().starts_with("HEAD: 0 commits"));
+    }
+
+    #[test]
+    fn test_to_table() {
+        let table = sample().to_table();
+
+        assert!(table.starts_with("HEAD: 5 commits, 1 without gitmoji (20.0%)\n"));
+        assert!(table.contains("\n:bug:                              2  40.0%\n"));
+        assert!(table.contains("\n(none)                             1  20.0%\n"));
+        assert!(table.contains("\nAda                            3      33.3%  :bug:\n"));
+        assert!(table.contains("\n2026-02          2        0      2          0\n"));
+    }
+
+    #[test]
+    fn test_to_json() {
+        let json: serde_json::Value = serde_json::from_str(&sample().to_json().unwrap()).unwrap();
+
+        assert_eq!(json["commits"], 5);
+        assert_eq!(json["without_gitmoji"], 1);
+        assert_eq!(json["period"], "month");
+        assert_eq!(json["authors"][0]["author"], "Ada");
+        assert_eq!(json["trend"][0]["period"], "2026-01");
+        assert_eq!(json["sections"][0]["section"], "features");
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let mut stats = sample();
+        stats.authors[1].author = "Hopper, Grace".to_string();
+        let csv = stats.to_csv();
+
+        assert!(csv.starts_with("group,name,gitmoji,commits\ntotal,,:bug:,2\n"));
+        assert!(csv.contains("\ntotal,,none,1\n"));
+        assert!(csv.contains("\nauthor,Ada,none,1\n"));
+        assert!(csv.contains("\nauthor,\"Hopper, Grace\",:sparkles:,1\n"));
+        assert!(csv.contains("\nmonth,2026-02,:bug:,2\n"));
+    }
+}
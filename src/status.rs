@@ -0,0 +1,358 @@
+use anyhow::{Result, bail};
+
+/// State of a path in the index or the work tree, from one column of the porcelain `XY` field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileState {
+    Unmodified,
+    Modified,
+    TypeChanged,
+    Added,
+    Deleted,
+    Renamed,
+    Copied,
+    Unmerged,
+}
+
+impl FileState {
+    /// Parse one column of the `XY` field
+    pub fn parse(c: char) -> Option<Self> {
+        match c {
+            '.' => Some(Self::Unmodified),
+            'M' => Some(Self::Modified),
+            'T' => Some(Self::TypeChanged),
+            'A' => Some(Self::Added),
+            'D' => Some(Self::Deleted),
+            'R' => Some(Self::Renamed),
+            'C' => Some(Self::Copied),
+            'U' => Some(Self::Unmerged),
+            _ => None,
+        }
+    }
+}
+
+/// The kind of record a status entry came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    /// A changed tracked path (`1` record)
+    Changed,
+    /// A renamed or copied path (`2` record)
+    RenamedOrCopied,
+    /// A path with merge conflicts (`u` record)
+    Unmerged,
+    /// An untracked path (`?` record)
+    Untracked,
+    /// An ignored path (`!` record)
+    Ignored,
+}
+
+/// Which side of a merge touched a conflicted path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictKind {
+    BothDeleted,
+    AddedByUs,
+    DeletedByThem,
+    AddedByThem,
+    DeletedByUs,
+    BothAdded,
+    BothModified,
+}
+
+impl ConflictKind {
+    /// Parse the `XY` field of an unmerged entry
+    pub fn parse(xy: &str) -> Option<Self> {
+        match xy {
+            "DD" => Some(Self::BothDeleted),
+            "AU" => Some(Self::AddedByUs),
+            "UD" => Some(Self::DeletedByThem),
+            "UA" => Some(Self::AddedByThem),
+            "DU" => Some(Self::DeletedByUs),
+            "AA" => Some(Self::BothAdded),
+            "UU" => Some(Self::BothModified),
+            _ => None,
+        }
+    }
+}
+
+/// Submodule flags from the `S<c><m><u>` field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubmoduleState {
+    pub commit_changed: bool,
+    pub has_tracked_changes: bool,
+    pub has_untracked_changes: bool,
+}
+
+impl SubmoduleState {
+    /// Parse the `<sub>` field, returning None for regular files (`N...`)
+    pub fn parse(field: &str) -> Result<Option<Self>> {
+        let flags: Vec<char> = field.chars().collect();
+        match flags.as_slice() {
+            ['N', '.', '.', '.'] => Ok(None),
+            ['S', c, m, u] => Ok(Some(Self {
+                commit_changed: *c == 'C',
+                has_tracked_changes: *m == 'M',
+                has_untracked_changes: *u == 'U',
+            })),
+            _ => bail!("Invalid submodule state '{field}' in git status output"),
+        }
+    }
+}
+
+/// One path reported by `git status --porcelain=v2 -z`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusEntry {
+    pub kind: EntryKind,
+    pub index: FileState,
+    pub worktree: FileState,
+    pub path: String,
+    /// The source path of a rename or copy
+    pub original_path: Option<String>,
+    /// Similarity score of a rename or copy, in percent
+    pub score: Option<u8>,
+    pub submodule: Option<SubmoduleState>,
+    pub conflict: Option<ConflictKind>,
+}
+
+impl StatusEntry {
+    /// Parse the NUL-separated output of `git status --porcelain=v2 -z`
+    pub fn parse_porcelain_v2(output: &str) -> Result<Vec<StatusEntry>> {
+        let mut entries = Vec::new();
+        let mut records = output.split('\0').filter(|record| !record.is_empty());
+
+        while let Some(record) = records.next() {
+            let entry = match record.split_at_checked(2) {
+                Some(("1 ", rest)) => Self::parse_changed(rest)?,
+                Some(("2 ", rest)) => {
+                    // With -z the rename source follows as its own record
+                    let Some(original_path) = records.next() else {
+                        bail!("Missing rename source for '{record}' in git status output");
+                    };
+                    Self::parse_renamed(rest, original_path)?
+                }
+                Some(("u ", rest)) => Self::parse_unmerged(rest)?,
+                Some(("? ", path)) => Self::untracked(EntryKind::Untracked, path),
+                Some(("! ", path)) => Self::untracked(EntryKind::Ignored, path),
+                // Headers such as `# branch.oid` are only emitted on request
+                _ if record.starts_with("# ") => continue,
+                _ => bail!("Unexpected record '{record}' in git status output"),
+            };
+            entries.push(entry);
+        }
+
+        Ok(entries)
+    }
+
+    /// Whether the index holds a change for this path that a commit would record
+    pub fn is_staged(&self) -> bool {
+        matches!(self.kind, EntryKind::Changed | EntryKind::RenamedOrCopied)
+            && self.index != FileState::Unmodified
+    }
+
+    /// Whether the work tree has changes for this path that are not staged
+    pub fn has_unstaged_changes(&self) -> bool {
+        matches!(self.kind, EntryKind::Changed | EntryKind::RenamedOrCopied)
+            && self.worktree != FileState::Unmodified
+    }
+
+    /// Whether this path still has merge conflicts
+    pub fn is_unmerged(&self) -> bool {
+        self.kind == EntryKind::Unmerged
+    }
+
+    /// Whether this path is a submodule
+    pub fn is_submodule(&self) -> bool {
+        self.submodule.is_some()
+    }
+
+    /// `1 <XY> <sub> <mH> <mI> <mW> <hH> <hI> <path>`
+    fn parse_changed(rest: &str) -> Result<Self> {
+        let fields: Vec<&str> = rest.splitn(8, ' ').collect();
+        let [xy, sub, _, _, _, _, _, path] = fields[..] else {
+            bail!("Malformed changed entry '1 {rest}' in git status output");
+        };
+        let (index, worktree) = Self::parse_xy(xy)?;
+
+        Ok(Self {
+            kind: EntryKind::Changed,
+            index,
+            worktree,
+            path: path.to_string(),
+            original_path: None,
+            score: None,
+            submodule: SubmoduleState::parse(sub)?,
+            conflict: None,
+        })
+    }
+
+    /// `2 <XY> <sub> <mH> <mI> <mW> <hH> <hI> <X><score> <path>`
+    fn parse_renamed(rest: &str, original_path: &str) -> Result<Self> {
+        let fields: Vec<&str> = rest.splitn(9, ' ').collect();
+        let [xy, sub, _, _, _, _, _, score, path] = fields[..] else {
+            bail!("Malformed rename entry '2 {rest}' in git status output");
+        };
+        let (index, worktree) = Self::parse_xy(xy)?;
+        let Some(score) = score.get(1..).and_then(|s| s.parse().ok()) else {
+            bail!("Invalid rename score '{score}' in git status output");
+        };
+
+        Ok(Self {
+            kind: EntryKind::RenamedOrCopied,
+            index,
+            worktree,
+            path: path.to_string(),
+            original_path: Some(original_path.to_string()),
+            score: Some(score),
+            submodule: SubmoduleState::parse(sub)?,
+            conflict: None,
+        })
+    }
+
+    /// `u <XY> <sub> <m1> <m2> <m3> <mW> <h1> <h2> <h3> <path>`
+    fn parse_unmerged(rest: &str) -> Result<Self> {
+        let fields: Vec<&str> = rest.splitn(10, ' ').collect();
+        let [xy, sub, _, _, _, _, _, _, _, path] = fields[..] else {
+            bail!("Malformed unmerged entry 'u {rest}' in git status output");
+        };
+        let (index, worktree) = Self::parse_xy(xy)?;
+        let Some(conflict) = ConflictKind::parse(xy) else {
+            bail!("Invalid conflict state '{xy}' in git status output");
+        };
+
+        Ok(Self {
+            kind: EntryKind::Unmerged,
+            index,
+            worktree,
+            path: path.to_string(),
+            original_path: None,
+            score: None,
+            submodule: SubmoduleState::parse(sub)?,
+            conflict: Some(conflict),
+        })
+    }
+
+    fn untracked(kind: EntryKind, path: &str) -> Self {
+        Self {
+            kind,
+            index: FileState::Unmodified,
+            worktree: FileState::Unmodified,
+            path: path.to_string(),
+            original_path: None,
+            score: None,
+            submodule: None,
+            conflict: None,
+        }
+    }
+
+    fn parse_xy(xy: &str) -> Result<(FileState, FileState)> {
+        let mut chars = xy.chars();
+        match (
+            chars.next().and_then(FileState::parse),
+            chars.next().and_then(FileState::parse),
+            chars.next(),
+        ) {
+            (Some(index), Some(worktree), None) => Ok((index, worktree)),
+            _ => bail!("Invalid state '{xy}' in git status output"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODES: &str = "100644 100644 100644";
+    const HASHES: &str =
+        "1111111111111111111111111111111111111111 2222222222222222222222222222222222222222";
+
+    #[test]
+    fn test_parse_changed_entries() {
+        let output = format!(
+            "1 M. N... {MODES} {HASHES} src/lib.rs\0\
+             1 .M N... {MODES} {HASHES} README.md\0\
+             1 T. N... {MODES} {HASHES} bin/tool\0\
+             1 A. N... {MODES} {HASHES} docs/my notes \"draft\".md\0"
+        );
+
+        let entries = StatusEntry::parse_porcelain_v2(&output).unwrap();
+        assert_eq!(entries.len(), 4);
+
+        assert_eq!(entries[0].kind, EntryKind::Changed);
+        assert_eq!(entries[0].index, FileState::Modified);
+        assert_eq!(entries[0].worktree, FileState::Unmodified);
+        assert!(entries[0].is_staged());
+        assert!(!entries[0].has_unstaged_changes());
+
+        assert!(!entries[1].is_staged());
+        assert!(entries[1].has_unstaged_changes());
+
+        // Type changes count as staged
+        assert_eq!(entries[2].index, FileState::TypeChanged);
+        assert!(entries[2].is_staged());
+
+        // Paths keep spaces and quotes verbatim
+        assert_eq!(entries[3].path, "docs/my notes \"draft\".md");
+    }
+
+    #[test]
+    fn test_parse_renamed_entry() {
+        let output =
+            format!("2 R. N... {MODES} {HASHES} R87 new name.rs\0old name.rs\0? scratch.txt\0");
+
+        let entries = StatusEntry::parse_porcelain_v2(&output).unwrap();
+        assert_eq!(entries.len(), 2);
+
+        assert_eq!(entries[0].kind, EntryKind::RenamedOrCopied);
+        assert_eq!(entries[0].index, FileState::Renamed);
+        assert_eq!(entries[0].path, "new name.rs");
+        assert_eq!(entries[0].original_path.as_deref(), Some("old name.rs"));
+        assert_eq!(entries[0].score, Some(87));
+        assert!(entries[0].is_staged());
+
+        assert_eq!(entries[1].kind, EntryKind::Untracked);
+        assert_eq!(entries[1].path, "scratch.txt");
+        assert!(!entries[1].is_staged());
+    }
+
+    #[test]
+    fn test_parse_unmerged_and_submodule_entries() {
+        let output = format!(
+            "u UU N... 100644 100644 100644 100644 {HASHES} 3333333333333333333333333333333333333333 src/main.rs\0\
+             1 .M SCMU 160000 160000 160000 {HASHES} vendor/lib\0\
+             ! target/\0"
+        );
+
+        let entries = StatusEntry::parse_porcelain_v2(&output).unwrap();
+        assert_eq!(entries.len(), 3);
+
+        assert!(entries[0].is_unmerged());
+        assert_eq!(entries[0].conflict, Some(ConflictKind::BothModified));
+        assert!(!entries[0].is_staged());
+
+        assert!(entries[1].is_submodule());
+        assert_eq!(
+            entries[1].submodule,
+            Some(SubmoduleState {
+                commit_changed: true,
+                has_tracked_changes: true,
+                has_untracked_changes: true,
+            })
+        );
+
+        assert_eq!(entries[2].kind, EntryKind::Ignored);
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_output() {
+        assert!(StatusEntry::parse_porcelain_v2("").unwrap().is_empty());
+        assert!(
+            StatusEntry::parse_porcelain_v2("# branch.oid abc\0")
+                .unwrap()
+                .is_empty()
+        );
+        assert!(StatusEntry::parse_porcelain_v2("M  src/lib.rs\0").is_err());
+        assert!(StatusEntry::parse_porcelain_v2("1 M. N... truncated\0").is_err());
+        assert!(
+            StatusEntry::parse_porcelain_v2(&format!("2 R. N... {MODES} {HASHES} R90 new.rs\0"))
+                .is_err()
+        );
+    }
+}
@@ -1,4 +1,10 @@
-use auto_gitmoji::{commit::GitCommit, emoji::EmojiLookup, matcher::MatcherFactory};
+use auto_gitmoji::{
+    backend::ProcessBackend,
+    commit::{GitCommit, GitError},
+    diff::{DiffAnalyzer, summary::DEFAULT_BUDGET},
+    emoji::EmojiLookup,
+    matcher::{GenerationContext, MatcherFactory},
+};
 use std::process::Command;
 
 #[test]
@@ -40,6 +46,8 @@ fn test_full_workflow_integration() {
 
     // 1. Create matcher
     let matcher = MatcherFactory::simple();
+    let backend = ProcessBackend::new();
+    let git = GitCommit::new(&backend);
 
     // 2. Test various commit message types
     let test_cases = vec![
@@ -68,7 +76,7 @@ fn test_full_workflow_integration() {
         assert!(format_message.contains(message));
 
         // 6. Test dry run commit
-        let commit_result = GitCommit::commit(&format_message, true);
+        let commit_result = git.commit(&format_message, true);
         assert!(commit_result.is_ok());
 
         let dry_run_output = commit_result.unwrap();
@@ -205,6 +213,8 @@ fn test_git_integration_safety() {
     // Test that Git operations are safe and don't cause issues
 
     // Test dry run always works
+    let backend = ProcessBackend::new();
+    let git = GitCommit::new(&backend);
     let messages = vec![
         "test commit message",
         ":sparkles: add new feature",
@@ -215,7 +225,7 @@ fn test_git_integration_safety() {
     ];
 
     for message in messages {
-        let result = GitCommit::commit(message, true);
+        let result = git.commit(message, true);
         assert!(result.is_ok(), "Dry run should work for: '{message}'");
 
         let output = result.unwrap();
@@ -223,8 +233,9 @@ fn test_git_integration_safety() {
             output.contains("DRY RUN"),
             "Should indicate dry run for: '{message}'"
         );
+        // The message is shell-quoted, apostrophes included
         assert!(
-            output.contains(message),
+            output.contains(&message.replace('\'', r"'\''")),
             "Should contain message for: '{message}'"
         );
     }
@@ -234,6 +245,8 @@ fn test_git_integration_safety() {
 fn test_performance_integration() {
     // Test performance of the integrated system
     let matcher = MatcherFactory::simple();
+    let backend = ProcessBackend::new();
+    let git = GitCommit::new(&backend);
 
     let start = std::time::Instant::now();
 
@@ -257,7 +270,7 @@ fn test_performance_integration() {
 
             // The format_message already contains the full formatted message
             // Dry run commit
-            let _commit_result = GitCommit::commit(&format_message, true).unwrap();
+            let _commit_result = git.commit(&format_message, true).unwrap();
         }
     }
 
@@ -314,3 +327,178 @@ fn test_data_file_loading() {
         );
     }
 }
+
+/// Create an empty repository with a committer identity in a fresh temp directory
+fn temp_repo(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("amoji-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    for args in [
+        &["init", "-q"][..],
+        &["config", "user.name", "amoji"],
+        &["config", "user.email", "amoji@example.com"],
+    ] {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(&dir)
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+    dir
+}
+
+#[test]
+fn test_repository_context_targets_nested_repo() {
+    let repo = temp_repo("repo-context");
+    std::fs::write(repo.join("notes file.md"), "hello\n").unwrap();
+
+    let backend = ProcessBackend::in_repo(&repo);
+    let git = GitCommit::new(&backend);
+    assert!(git.is_git_repository().unwrap());
+    assert!(!git.has_staged_changes().unwrap());
+
+    backend.run_checked(&["add", "notes file.md"]).unwrap();
+    let status = git.status().unwrap();
+    assert_eq!(status.len(), 1);
+    assert_eq!(status[0].path, "notes file.md");
+    assert!(git.has_staged_changes().unwrap());
+
+    git.commit(":memo: add notes", false).unwrap();
+    let commits = git.log("HEAD").unwrap();
+    assert_eq!(commits.len(), 1);
+    assert_eq!(commits[0].subject, ":memo: add notes");
+
+    // Hooks resolve inside the target repository, not the current directory
+    assert_eq!(git.hooks_dir().unwrap(), repo.join(".git/hooks"));
+
+    let _ = std::fs::remove_dir_all(&repo);
+}
+
+#[test]
+fn test_repository_context_linked_worktree() {
+    let repo = temp_repo("repo-worktree");
+    let worktree = repo.with_file_name(format!(
+        "{}-wt",
+        repo.file_name().unwrap().to_string_lossy()
+    ));
+    let _ = std::fs::remove_dir_all(&worktree);
+
+    let backend = ProcessBackend::in_repo(&repo);
+    backend
+        .run_checked(&["commit", "-q", "--allow-empty", "-m", ":tada: init"])
+        .unwrap();
+    backend
+        .run_checked(&["worktree", "add", "-q", &worktree.to_string_lossy()])
+        .unwrap();
+
+    let backend = ProcessBackend::in_repo(&worktree);
+    let git = GitCommit::new(&backend);
+    assert!(git.is_git_repository().unwrap());
+    assert_eq!(git.log("HEAD").unwrap()[0].subject, ":tada: init");
+
+    // Linked worktrees share the hooks of the main repository
+    assert_eq!(
+        std::fs::canonicalize(git.hooks_dir().unwrap()).ok(),
+        std::fs::canonicalize(repo.join(".git/hooks")).ok()
+    );
+
+    let _ = std::fs::remove_dir_all(&worktree);
+    let _ = std::fs::remove_dir_all(&repo);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_hook_rejection_is_told_apart_from_git_errors() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let repo = temp_repo("hook-rejection");
+    let hook = repo.join(".git/hooks/pre-commit");
+    std::fs::write(&hook, "#!/bin/sh\necho 'error: lint failed' >&2\nexit 1\n").unwrap();
+    std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+    std::fs::write(repo.join("lib.rs"), "fn main() {}\n").unwrap();
+
+    let backend = ProcessBackend::in_repo(&repo);
+    backend.run_checked(&["add", "lib.rs"]).unwrap();
+    let git = GitCommit::new(&backend);
+    let classify = |extra_args: &[&str]| {
+        let extra_args: Vec<String> = extra_args.iter().map(|arg| arg.to_string()).collect();
+        let error = git
+            .commit_with_args(":sparkles: add lib", &extra_args, false)
+            .unwrap_err();
+        error.downcast::<GitError>().unwrap()
+    };
+
+    // The hook's output looks like git's own, but git reports that the hook failed
+    match classify(&[]) {
+        GitError::HookRejected(output) => assert_eq!(output, "error: lint failed"),
+        error => panic!("expected a hook rejection, got {error:?}"),
+    }
+    // Git fails on a bad pathspec before running any hook
+    assert!(matches!(
+        classify(&["--", "missing.rs"]),
+        GitError::CommandFailed(_)
+    ));
+
+    let _ = std::fs::remove_dir_all(&repo);
+}
+
+#[test]
+fn test_cli_repo_option_before_subcommand() {
+    let repo = temp_repo("cli-repo-option");
+    ProcessBackend::in_repo(&repo)
+        .run_checked(&["commit", "-q", "--allow-empty", "-m", ":tada: init"])
+        .unwrap();
+    let amoji = |args: &[&str]| {
+        let output = Command::new("cargo")
+            .args(["run", "-q", "--bin", "amoji", "--"])
+            .args(args)
+            .output()
+            .expect("Failed to run amoji");
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    let repo_path = repo.to_string_lossy();
+
+    // A leading -C still allows a subcommand, wherever it is given
+    assert!(amoji(&["-C", &repo_path, "log"]).contains("🎉 init"));
+    assert!(amoji(&["log", "-C", &repo_path]).contains("🎉 init"));
+
+    // Other options before a subcommand name make it the message
+    let output = amoji(&["-C", &repo_path, "--dry-run", "log"]);
+    assert!(output.contains("git commit -m ':sparkles: log'"));
+
+    let _ = std::fs::remove_dir_all(&repo);
+}
+
+#[test]
+fn test_generate_message_from_staged_diff() {
+    let repo = temp_repo("generate");
+    std::fs::create_dir_all(repo.join("docs")).unwrap();
+    std::fs::write(repo.join("docs/install.md"), "cargo install\n").unwrap();
+    std::fs::write(repo.join("docs/usage.md"), "amoji \"fix bug\"\n").unwrap();
+
+    let backend = ProcessBackend::in_repo(&repo);
+    backend.run_checked(&["add", "docs"]).unwrap();
+    let git = GitCommit::new(&backend);
+
+    let diff = DiffAnalyzer::staged_summary(&git, DEFAULT_BUDGET).unwrap();
+    assert_eq!(diff.files.len(), 2);
+    assert!(diff.text.contains("+cargo install"));
+
+    let signals = DiffAnalyzer::staged_signals(&git).unwrap();
+    let matcher = MatcherFactory::diff_aware(MatcherFactory::simple(), signals.clone());
+    let context = GenerationContext {
+        diff,
+        signals,
+        hint: None,
+    };
+    let message = matcher.generate_message(&context).unwrap().unwrap();
+    assert_eq!(
+        message.to_message(),
+        ":memo: update docs/install.md and docs/usage.md\n\n- docs/install.md (+1 -0)\n- docs/usage.md (+1 -0)"
+    );
+
+    let _ = std::fs::remove_dir_all(&repo);
+}